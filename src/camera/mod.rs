@@ -26,7 +26,8 @@ pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<CameraMoveEvent>()
+        app.init_resource::<CameraFocus>()
+            .add_event::<CameraMoveEvent>()
             .add_event::<CameraZoomEvent>()
             .add_event::<CameraTransitionEvent>()
             .add_systems(Startup, setup_camera)
@@ -36,6 +37,7 @@ impl Plugin for CameraPlugin {
                     .after(PhysicsSet::Writeback)
                     .after(switch_level)
                     .run_if(not(in_state(GameState::Animating)))
+                    .run_if(not(in_state(GameState::Cutscene)))
                     .in_set(LevelSystems::Simulation),
             )
             .add_systems(
@@ -474,22 +476,31 @@ pub fn camera_position_from_level(level_box: Rect, player_pos: Vec2) -> Vec2 {
     camera_position_from_level_with_scale(level_box, player_pos, 1.)
 }
 
-/// [`System`] that moves camera to player's position and constrains it to the [`CurrentLevel`]'s `world_box`.
+/// [`Resource`] holding the point the camera frames instead of the player, if any.
+#[derive(Resource, Default, Debug)]
+pub struct CameraFocus(pub Option<Vec2>);
+
+/// [`System`] that moves camera to player's position (or the [`CameraFocus`]) and constrains it to
+/// the [`CurrentLevel`]'s `world_box`.
 pub fn move_camera(
     current_level: Res<CurrentLevel>,
+    camera_focus: Res<CameraFocus>,
     q_player: Query<&Transform, With<PlayerMarker>>,
-    q_camera: Query<&Transform, With<MainCamera>>,
+    q_camera: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
     mut ev_move_camera: EventWriter<CameraMoveEvent>,
 ) {
     let Ok(player_transform) = q_player.get_single() else {
         return;
     };
-    let Ok(camera_transform) = q_camera.get_single() else {
+    let Ok((camera_transform, camera_projection)) = q_camera.get_single() else {
         return;
     };
 
-    let camera_pos =
-        camera_position_from_level(current_level.level_box, player_transform.translation.xy());
+    let camera_pos = camera_position_from_level_with_scale(
+        current_level.level_box,
+        camera_focus.0.unwrap_or(player_transform.translation.xy()),
+        camera_projection.scale,
+    );
     ev_move_camera.send(CameraMoveEvent {
         to: camera_transform.translation.xy().lerp(camera_pos, 0.2),
         variant: CameraControlType::Instant,
@@ -35,6 +35,23 @@ impl Default for Config {
                 key_left: KeyCode::KeyA,
                 key_right: KeyCode::KeyD,
                 key_jump: KeyCode::Space,
+                // Light
+                key_snap: default_key_snap(),
+                key_colors: default_key_colors(),
+                key_recall: default_key_recall(),
+                key_grapple: default_key_grapple(),
+                key_aim_ccw: default_key_aim_ccw(),
+                key_aim_cw: default_key_aim_cw(),
+                key_aim_fire: default_key_aim_fire(),
+                aim_speed: default_aim_speed(),
+                // Characters
+                key_interact: default_key_interact(),
+                // Hints
+                key_dismiss_hint: default_key_dismiss_hint(),
+                // Assists
+                key_quick_reset: default_key_quick_reset(),
+                key_rewind: default_key_rewind(),
+                key_solution_hint: default_key_solution_hint(),
             },
         }
     }
@@ -44,6 +61,9 @@ impl Default for Config {
 pub struct DebugConfig {
     pub ui: bool,
     pub unlock_levels: bool,
+    /// Draws the wires between sensors, logic gates and their targets
+    #[serde(default)]
+    pub logic_wires: bool,
 }
 
 #[derive(Deserialize)]
@@ -59,4 +79,95 @@ pub struct ControlsConfig {
     pub key_right: KeyCode,
     pub key_left: KeyCode,
     pub key_jump: KeyCode,
+    // Light
+    #[serde(default = "default_key_snap")]
+    pub key_snap: KeyCode,
+    /// The keys that select each color, in the order Green, Purple, White, Blue, Black
+    #[serde(default = "default_key_colors")]
+    pub key_colors: [KeyCode; 5],
+    #[serde(default = "default_key_recall")]
+    pub key_recall: KeyCode,
+    #[serde(default = "default_key_grapple")]
+    pub key_grapple: KeyCode,
+    // Keyboard aiming
+    #[serde(default = "default_key_aim_ccw")]
+    pub key_aim_ccw: KeyCode,
+    #[serde(default = "default_key_aim_cw")]
+    pub key_aim_cw: KeyCode,
+    #[serde(default = "default_key_aim_fire")]
+    pub key_aim_fire: KeyCode,
+    /// How fast holding an aim key rotates the aim, in degrees per second
+    #[serde(default = "default_aim_speed")]
+    pub aim_speed: f32,
+    // Characters
+    #[serde(default = "default_key_interact")]
+    pub key_interact: KeyCode,
+    // Hints
+    #[serde(default = "default_key_dismiss_hint")]
+    pub key_dismiss_hint: KeyCode,
+    // Assists
+    #[serde(default = "default_key_quick_reset")]
+    pub key_quick_reset: KeyCode,
+    #[serde(default = "default_key_rewind")]
+    pub key_rewind: KeyCode,
+    #[serde(default = "default_key_solution_hint")]
+    pub key_solution_hint: KeyCode,
+}
+
+fn default_key_snap() -> KeyCode {
+    KeyCode::ShiftLeft
+}
+
+fn default_key_colors() -> [KeyCode; 5] {
+    [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+    ]
+}
+
+fn default_key_recall() -> KeyCode {
+    KeyCode::KeyQ
+}
+
+fn default_key_grapple() -> KeyCode {
+    KeyCode::KeyE
+}
+
+fn default_key_aim_ccw() -> KeyCode {
+    KeyCode::KeyJ
+}
+
+fn default_key_aim_cw() -> KeyCode {
+    KeyCode::KeyL
+}
+
+fn default_key_aim_fire() -> KeyCode {
+    KeyCode::KeyK
+}
+
+fn default_aim_speed() -> f32 {
+    120.0
+}
+
+fn default_key_interact() -> KeyCode {
+    KeyCode::KeyF
+}
+
+fn default_key_dismiss_hint() -> KeyCode {
+    KeyCode::KeyH
+}
+
+fn default_key_quick_reset() -> KeyCode {
+    KeyCode::KeyR
+}
+
+fn default_key_rewind() -> KeyCode {
+    KeyCode::KeyT
+}
+
+fn default_key_solution_hint() -> KeyCode {
+    KeyCode::KeyG
 }
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{ecs::system::SystemId, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    camera::{
+        camera_position_from_level_with_scale, CameraControlType, CameraMoveEvent, CameraZoomEvent,
+    },
+    level::CurrentLevel,
+    player::{InputLocked, PlayerMarker},
+    shared::GameState,
+    sound::{BgmMarker, Fade, FadeSettings},
+    ui::dialogue::StartDialogueEvent,
+};
+
+/// The [`CutsceneTable`], embedded at compile time so it is also there on the web build.
+const CUTSCENE_TABLE: &str = include_str!("../assets/cutscenes.toml");
+
+/// [`Plugin`] that loads the [`CutsceneTable`] and runs the cutscenes started with
+/// [`StartCutsceneEvent`].
+pub struct CutscenePlugin;
+
+impl Plugin for CutscenePlugin {
+    fn build(&self, app: &mut App) {
+        let table: CutsceneTable =
+            toml::from_str(CUTSCENE_TABLE).expect("Failed to parse cutscenes.toml");
+        app.insert_resource(table)
+            .init_resource::<CutsceneCallbacks>()
+            .add_event::<StartCutsceneEvent>()
+            .add_event::<CutsceneSignal>()
+            .add_systems(
+                Update,
+                (
+                    start_cutscene.run_if(on_event::<StartCutsceneEvent>),
+                    run_cutscene.run_if(resource_exists::<ActiveCutscene>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// [`Resource`] holding every cutscene, read from `assets/cutscenes.toml`.
+#[derive(Deserialize, Resource, Default, Debug)]
+#[serde(default)]
+pub struct CutsceneTable {
+    cutscenes: HashMap<String, Cutscene>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Cutscene {
+    steps: Vec<CutsceneStep>,
+}
+
+/// One step of a [`Cutscene`], see `assets/cutscenes.toml` for what each of them does.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "action")]
+enum CutsceneStep {
+    Camera {
+        scale: f32,
+        seconds: f32,
+        #[serde(default)]
+        ease: CutsceneEase,
+        to: Option<Vec2>,
+    },
+    Dialogue {
+        id: Option<String>,
+    },
+    Wait {
+        seconds: f32,
+    },
+    FadeBgm {
+        volume: f32,
+        seconds: f32,
+    },
+    LockInput,
+    UnlockInput,
+    PlaySound {
+        path: String,
+    },
+    Signal {
+        name: String,
+    },
+}
+
+/// The [`EaseFunction`]s a [`CutsceneStep::Camera`] can use.
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+enum CutsceneEase {
+    Linear,
+    QuadraticInOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    #[default]
+    SineInOut,
+}
+
+impl From<CutsceneEase> for EaseFunction {
+    fn from(value: CutsceneEase) -> Self {
+        match value {
+            CutsceneEase::Linear => EaseFunction::Linear,
+            CutsceneEase::QuadraticInOut => EaseFunction::QuadraticInOut,
+            CutsceneEase::CubicInOut => EaseFunction::CubicInOut,
+            CutsceneEase::SineIn => EaseFunction::SineIn,
+            CutsceneEase::SineOut => EaseFunction::SineOut,
+            CutsceneEase::SineInOut => EaseFunction::SineInOut,
+        }
+    }
+}
+
+/// [`Event`] that starts the cutscene with the id `id`, unless one is already running.
+/// `dialogue` is played by the cutscene's dialogue steps that don't name one.
+#[derive(Event)]
+pub struct StartCutsceneEvent {
+    pub id: String,
+    pub dialogue: Option<String>,
+}
+
+/// [`Event`] sent by the signal steps of a cutscene, so that the game can react at the right point
+/// of it.
+#[derive(Event)]
+pub struct CutsceneSignal(pub String);
+
+/// What the [`ActiveCutscene`] is waiting for before running its next step.
+enum CutsceneWait {
+    Nothing,
+    Timer(Timer),
+    Dialogue,
+}
+
+/// [`Resource`] that exists while a cutscene is running.
+#[derive(Resource)]
+pub struct ActiveCutscene {
+    id: String,
+    dialogue: Option<String>,
+    next_step: usize,
+    wait: CutsceneWait,
+}
+
+#[derive(Resource)]
+pub struct CutsceneCallbacks {
+    dialogue_finished: SystemId,
+}
+
+impl FromWorld for CutsceneCallbacks {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            dialogue_finished: world.register_system(finish_cutscene_dialogue),
+        }
+    }
+}
+
+/// [`System`] that starts the cutscene of a [`StartCutsceneEvent`] if none is running.
+pub fn start_cutscene(
+    mut commands: Commands,
+    mut ev_start_cutscene: EventReader<StartCutsceneEvent>,
+    table: Res<CutsceneTable>,
+    active_cutscene: Option<Res<ActiveCutscene>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(StartCutsceneEvent { id, dialogue }) = ev_start_cutscene.read().last() else {
+        return;
+    };
+    if active_cutscene.is_some() {
+        return;
+    }
+    if !table.cutscenes.contains_key(id) {
+        error!("Cutscene {id} doesn't exist");
+        return;
+    }
+    commands.insert_resource(ActiveCutscene {
+        id: id.clone(),
+        dialogue: dialogue.clone(),
+        next_step: 0,
+        wait: CutsceneWait::Nothing,
+    });
+    next_game_state.set(GameState::Cutscene);
+}
+
+/// Callback run when the dialogue of a [`CutsceneStep::Dialogue`] is closed.
+pub fn finish_cutscene_dialogue(active_cutscene: Option<ResMut<ActiveCutscene>>) {
+    if let Some(mut active_cutscene) = active_cutscene {
+        active_cutscene.wait = CutsceneWait::Nothing;
+    }
+}
+
+/// [`System`] that runs the steps of the [`ActiveCutscene`] until one of them has to be waited
+/// for, and ends the cutscene after its last step.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn run_cutscene(
+    mut commands: Commands,
+    time: Res<Time>,
+    table: Res<CutsceneTable>,
+    callbacks: Res<CutsceneCallbacks>,
+    mut cutscene: ResMut<ActiveCutscene>,
+    current_level: Res<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    q_player: Query<(Entity, &GlobalTransform), With<PlayerMarker>>,
+    q_bgm: Query<(&AudioSink, Entity, Option<&FadeSettings>), With<BgmMarker>>,
+    mut ev_move_camera: EventWriter<CameraMoveEvent>,
+    mut ev_zoom_camera: EventWriter<CameraZoomEvent>,
+    mut ev_start_dialogue: EventWriter<StartDialogueEvent>,
+    mut ev_signal: EventWriter<CutsceneSignal>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let waiting = match &mut cutscene.wait {
+        CutsceneWait::Nothing => false,
+        CutsceneWait::Timer(timer) => !timer.tick(time.delta()).finished(),
+        CutsceneWait::Dialogue => true,
+    };
+    if waiting {
+        return;
+    }
+    cutscene.wait = CutsceneWait::Nothing;
+
+    let steps = &table.cutscenes[&cutscene.id].steps;
+    while let CutsceneWait::Nothing = cutscene.wait {
+        let Some(step) = steps.get(cutscene.next_step) else {
+            if let Ok((player, _)) = q_player.get_single() {
+                commands.entity(player).remove::<InputLocked>();
+            }
+            commands.remove_resource::<ActiveCutscene>();
+            next_game_state.set(GameState::Playing);
+            return;
+        };
+        cutscene.next_step += 1;
+
+        match step {
+            CutsceneStep::Camera {
+                scale,
+                seconds,
+                ease,
+                to,
+            } => {
+                let duration = Duration::from_secs_f32(*seconds);
+                let player_pos = q_player
+                    .get_single()
+                    .map(|(_, transform)| transform.translation().xy());
+                let target = match (to, player_pos) {
+                    (Some(to), _) => Some(*to),
+                    (None, Ok(player_pos)) => Some(camera_position_from_level_with_scale(
+                        current_level.level_box,
+                        player_pos,
+                        *scale,
+                    )),
+                    (None, Err(_)) => None,
+                };
+                ev_zoom_camera.send(CameraZoomEvent {
+                    scale: *scale,
+                    variant: CameraControlType::Animated {
+                        duration,
+                        ease_fn: (*ease).into(),
+                        callback: None,
+                    },
+                });
+                if let Some(target) = target {
+                    ev_move_camera.send(CameraMoveEvent {
+                        to: target,
+                        variant: CameraControlType::Animated {
+                            duration,
+                            ease_fn: (*ease).into(),
+                            callback: None,
+                        },
+                    });
+                }
+                cutscene.wait = CutsceneWait::Timer(Timer::new(duration, TimerMode::Once));
+            }
+            CutsceneStep::Dialogue { id } => {
+                let Some(id) = id.clone().or_else(|| cutscene.dialogue.clone()) else {
+                    warn!(
+                        "Cutscene {} has a dialogue step without a dialogue",
+                        cutscene.id
+                    );
+                    continue;
+                };
+                ev_start_dialogue.send(StartDialogueEvent {
+                    id,
+                    on_end: callbacks.dialogue_finished,
+                });
+                cutscene.wait = CutsceneWait::Dialogue;
+            }
+            CutsceneStep::Wait { seconds } => {
+                cutscene.wait = CutsceneWait::Timer(Timer::from_seconds(*seconds, TimerMode::Once));
+            }
+            CutsceneStep::FadeBgm { volume, seconds } => {
+                for (sink, bgm, fade_settings) in q_bgm.iter() {
+                    // FIXME: If the entity has FadeSettings::Despawn fade just let it despawn
+                    if fade_settings.is_some_and(|settings| *settings == FadeSettings::Despawn) {
+                        continue;
+                    }
+                    commands.entity(bgm).insert(Fade::new(
+                        Duration::from_secs_f32(*seconds),
+                        sink.volume(),
+                        *volume,
+                    ));
+                }
+            }
+            CutsceneStep::LockInput => {
+                if let Ok((player, _)) = q_player.get_single() {
+                    commands.entity(player).insert(InputLocked);
+                }
+            }
+            CutsceneStep::UnlockInput => {
+                if let Ok((player, _)) = q_player.get_single() {
+                    commands.entity(player).remove::<InputLocked>();
+                }
+            }
+            CutsceneStep::PlaySound { path } => {
+                commands.spawn((
+                    AudioPlayer::new(asset_server.load(path)),
+                    PlaybackSettings::DESPAWN,
+                ));
+            }
+            CutsceneStep::Signal { name } => {
+                ev_signal.send(CutsceneSignal(name.clone()));
+            }
+        }
+    }
+}
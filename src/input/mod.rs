@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
+use enum_map::{Enum, EnumMap};
 
-use crate::camera::MainCamera;
+use crate::{camera::MainCamera, config::Config};
 
 /// [`Component`] that holds the position of the cursor, in world coordinates. You should query
 /// for this [`Component`] if you need the cursor position to do something. Note that if your
@@ -40,3 +41,117 @@ pub fn update_cursor_world_coords(
     };
     world_coords.pos = cursor_ray.origin.truncate();
 }
+
+/// Whether a [`HeldAction`] lasts for as long as its input is held down, or is switched on and off
+/// by pressing its input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    #[default]
+    Hold,
+    Toggle,
+}
+
+/// Actions that are performed by holding an input down, which can be changed to be toggled instead
+/// for players who can't hold an input for long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum HeldAction {
+    /// Aiming a beam, which is shot when the action ends (Left Click)
+    Shoot,
+    /// Snapping the aimed direction to fixed angles (Left Shift by default)
+    Snap,
+    /// Sneaking (Left Control)
+    Sneak,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct HeldActionState {
+    active: bool,
+    just_started: bool,
+    just_ended: bool,
+}
+
+/// [`Resource`] that tracks which [`HeldAction`]s are active, according to their [`InputMode`].
+/// Systems should read this instead of the raw inputs of these actions, and should be set to run
+/// after [`update_held_actions`].
+#[derive(Resource, Default, Debug)]
+pub struct HeldActions {
+    modes: EnumMap<HeldAction, InputMode>,
+    states: EnumMap<HeldAction, HeldActionState>,
+}
+
+impl HeldActions {
+    pub fn active(&self, action: HeldAction) -> bool {
+        self.states[action].active
+    }
+
+    pub fn just_started(&self, action: HeldAction) -> bool {
+        self.states[action].just_started
+    }
+
+    pub fn just_ended(&self, action: HeldAction) -> bool {
+        self.states[action].just_ended
+    }
+
+    /// Ends `action` without [`HeldActions::just_ended`] being set, so that e.g. a cancelled shot
+    /// isn't fired when the mouse button is released or clicked again.
+    pub fn cancel(&mut self, action: HeldAction) {
+        self.states[action].active = false;
+    }
+
+    /// Changes the [`InputMode`] of `action`, cancelling it so that a toggled action doesn't stay
+    /// active after switching back to holding.
+    pub fn set_mode(&mut self, action: HeldAction, mode: InputMode) {
+        self.modes[action] = mode;
+        self.cancel(action);
+    }
+}
+
+/// [`PreUpdate`] [`System`] that updates the [`HeldActions`] from this frame's inputs.
+pub fn update_held_actions(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    config: Res<Config>,
+    mut held_actions: ResMut<HeldActions>,
+) {
+    let key_snap = config.controls_config.key_snap;
+    let HeldActions { modes, states } = &mut *held_actions;
+    for (action, state) in states.iter_mut() {
+        let (just_pressed, just_released) = match action {
+            HeldAction::Shoot => (
+                mouse.just_pressed(MouseButton::Left),
+                mouse.just_released(MouseButton::Left),
+            ),
+            HeldAction::Snap => (keys.just_pressed(key_snap), keys.just_released(key_snap)),
+            HeldAction::Sneak => (
+                keys.just_pressed(KeyCode::ControlLeft),
+                keys.just_released(KeyCode::ControlLeft),
+            ),
+        };
+
+        let started = just_pressed && !state.active;
+        let ended = match modes[action] {
+            InputMode::Hold => just_released && (state.active || started),
+            InputMode::Toggle => just_pressed && state.active,
+        };
+        state.active = (state.active || started) && !ended;
+        state.just_started = started;
+        state.just_ended = ended;
+    }
+}
+
+/// Run condition that is true while `action` is active.
+pub fn held_action_active(action: HeldAction) -> impl FnMut(Res<HeldActions>) -> bool + Clone {
+    move |held_actions: Res<HeldActions>| held_actions.active(action)
+}
+
+/// Run condition that is true on the frame `action` starts.
+pub fn held_action_just_started(
+    action: HeldAction,
+) -> impl FnMut(Res<HeldActions>) -> bool + Clone {
+    move |held_actions: Res<HeldActions>| held_actions.just_started(action)
+}
+
+/// Run condition that is true on the frame `action` ends, unless it was cancelled.
+pub fn held_action_just_ended(action: HeldAction) -> impl FnMut(Res<HeldActions>) -> bool + Clone {
+    move |held_actions: Res<HeldActions>| held_actions.just_ended(action)
+}
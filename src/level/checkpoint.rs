@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{lighting::LineLight2d, player::PlayerHurtMarker, shared::ResetLevel};
+
+use super::{
+    crystal::on_crystal_changed,
+    entity::FixedEntityBundle,
+    entity_world_pos,
+    snapshot::{LevelSnapshot, LevelState},
+    LevelSystems,
+};
+
+/// [`Plugin`] for [`Checkpoint`]s, which the player respawns at instead of the level's start flag
+/// once they have touched them.
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveCheckpoint>()
+            .register_ldtk_entity::<CheckpointBundle>("Checkpoint")
+            .add_systems(
+                FixedUpdate,
+                activate_checkpoints
+                    .after(on_crystal_changed)
+                    .in_set(LevelSystems::Simulation),
+            )
+            .add_systems(
+                Update,
+                clear_checkpoint_on_level_switch.in_set(LevelSystems::Reset),
+            )
+            .add_systems(
+                Update,
+                restore_checkpoint
+                    .run_if(on_event::<ResetLevel>)
+                    .after(LevelSystems::Reset)
+                    .before(LevelSystems::Simulation),
+            )
+            .add_systems(
+                Update,
+                update_checkpoint_lights.run_if(resource_changed::<ActiveCheckpoint>),
+            );
+    }
+}
+
+/// Marker [`Component`] for checkpoints, which become active when the player touches them.
+#[derive(Default, Component)]
+pub struct Checkpoint;
+
+/// [`Bundle`] spawned in by Ldtk corresponding to checkpoints.
+#[derive(Bundle, LdtkEntity)]
+pub struct CheckpointBundle {
+    #[default]
+    checkpoint: Checkpoint,
+    #[sprite("lantern.png")]
+    sprite: Sprite,
+    #[with(checkpoint_light)]
+    light: LineLight2d,
+    #[from_entity_instance]
+    physics: FixedEntityBundle,
+    #[default]
+    sensor: Sensor,
+    #[from_entity_instance]
+    instance: EntityInstance,
+}
+
+const CHECKPOINT_LIGHT_COLOR: Vec4 = Vec4::new(1.0, 0.8627, 0.7176, 1.0);
+
+/// How bright the light of a [`Checkpoint`] is before it becomes active.
+const INACTIVE_CHECKPOINT_BRIGHTNESS: f32 = 0.2;
+
+pub fn checkpoint_light(_: &EntityInstance) -> LineLight2d {
+    LineLight2d::point(
+        (CHECKPOINT_LIGHT_COLOR.truncate() * INACTIVE_CHECKPOINT_BRIGHTNESS).extend(1.0),
+        50.0,
+        0.015,
+    )
+}
+
+/// The [`LevelSnapshot`] taken when a [`Checkpoint`] was reached, which is restored when the
+/// player respawns.
+#[derive(Debug)]
+struct CheckpointRecord {
+    checkpoint: Entity,
+    level_iid: LevelIid,
+    respawn_pos: Vec2,
+    snapshot: LevelSnapshot,
+}
+
+/// [`Resource`] that holds the last [`Checkpoint`] reached in the current level, if any. Cleared
+/// when the level switches. The record is shared so that rewinding can cheaply keep a copy of it
+/// every tick.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct ActiveCheckpoint(Option<Arc<CheckpointRecord>>);
+
+impl ActiveCheckpoint {
+    /// Where the player should respawn in the level with `level_iid`, if they have reached a
+    /// checkpoint there.
+    pub fn respawn_pos(&self, level_iid: &LevelIid) -> Option<Vec2> {
+        self.0
+            .as_ref()
+            .filter(|record| record.level_iid == *level_iid)
+            .map(|record| record.respawn_pos)
+    }
+}
+
+/// [`System`] that activates the [`Checkpoint`]s the player touches, taking a [`LevelSnapshot`]
+/// to restore on respawn.
+pub fn activate_checkpoints(
+    rapier_context: Query<&RapierContext>,
+    q_player: Query<Entity, With<PlayerHurtMarker>>,
+    q_checkpoints: Query<(Entity, &EntityInstance), With<Checkpoint>>,
+    level_state: LevelState,
+    mut active_checkpoint: ResMut<ActiveCheckpoint>,
+) {
+    let Ok(rapier_context) = rapier_context.get_single() else {
+        return;
+    };
+    let Ok(player_entity) = q_player.get_single() else {
+        return;
+    };
+    for (checkpoint, instance) in q_checkpoints.iter() {
+        if active_checkpoint
+            .0
+            .as_ref()
+            .is_some_and(|record| record.checkpoint == checkpoint)
+        {
+            continue;
+        }
+        let Some(true) = rapier_context.intersection_pair(player_entity, checkpoint) else {
+            continue;
+        };
+
+        active_checkpoint.0 = Some(Arc::new(CheckpointRecord {
+            checkpoint,
+            level_iid: level_state.current_level_iid().clone(),
+            respawn_pos: entity_world_pos(instance),
+            snapshot: level_state.snapshot(),
+        }));
+        return;
+    }
+}
+
+pub fn clear_checkpoint_on_level_switch(
+    mut ev_reset_level: EventReader<ResetLevel>,
+    mut active_checkpoint: ResMut<ActiveCheckpoint>,
+) {
+    if !ev_reset_level.read().any(|ev| *ev == ResetLevel::Switching) {
+        return;
+    }
+    active_checkpoint.0 = None;
+}
+
+/// [`System`] that restores the [`LevelSnapshot`] of the [`ActiveCheckpoint`] on respawn, after
+/// the rest of the level has been reset to its initial state.
+pub fn restore_checkpoint(
+    mut ev_reset_level: EventReader<ResetLevel>,
+    active_checkpoint: Res<ActiveCheckpoint>,
+    mut level_state: LevelState,
+) {
+    if !ev_reset_level.read().any(|ev| *ev == ResetLevel::Respawn) {
+        return;
+    }
+    let Some(record) = active_checkpoint.0.as_ref() else {
+        return;
+    };
+    if record.level_iid == *level_state.current_level_iid() {
+        level_state.restore(&record.snapshot);
+    }
+}
+
+/// [`System`] that lights up the [`ActiveCheckpoint`] and dims every other [`Checkpoint`].
+pub fn update_checkpoint_lights(
+    active_checkpoint: Res<ActiveCheckpoint>,
+    mut q_checkpoints: Query<(Entity, &mut LineLight2d), With<Checkpoint>>,
+) {
+    let active = active_checkpoint.0.as_ref().map(|record| record.checkpoint);
+    for (entity, mut light) in q_checkpoints.iter_mut() {
+        let brightness = if Some(entity) == active {
+            1.0
+        } else {
+            INACTIVE_CHECKPOINT_BRIGHTNESS
+        };
+        light.color = (CHECKPOINT_LIGHT_COLOR.truncate() * brightness).extend(1.0);
+    }
+}
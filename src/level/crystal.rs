@@ -11,6 +11,7 @@ use super::{
     entity::HurtMarker,
     merge_tile::{spawn_merged_tiles, MergedTile},
     sensor::update_light_sensors,
+    surface::Surface,
     CurrentLevel, LevelSystems,
 };
 
@@ -45,8 +46,7 @@ impl Plugin for CrystalPlugin {
                 on_crystal_changed
                     .in_set(LevelSystems::Simulation)
                     .after(update_light_sensors),
-            )
-            .add_systems(Update, reset_crystals.in_set(LevelSystems::Reset));
+            );
 
         for i in 3..=10 {
             app.register_ldtk_int_cell_for_layer::<CrystalBundle>("Terrain", i);
@@ -101,12 +101,13 @@ pub struct CrystalIdent {
 #[derive(Default, Component)]
 pub struct Crystal {
     pub ident: CrystalIdent,
-    init_active: bool,
+    pub init_active: bool,
     pub active: bool,
+    surface: Surface,
 }
 
 impl MergedTile for Crystal {
-    type CompareData = (CrystalIdent, bool);
+    type CompareData = (CrystalIdent, bool, Surface);
 
     fn bundle(
         commands: &mut EntityCommands,
@@ -114,7 +115,7 @@ impl MergedTile for Crystal {
         half_extent: Vec2,
         compare_data: &Self::CompareData,
     ) {
-        let (crystal_color, crystal_active) = compare_data;
+        let (crystal_color, crystal_active, surface) = compare_data;
 
         if crystal_color.color == CrystalColor::Blue {
             commands.insert(CollisionGroups::new(
@@ -138,15 +139,17 @@ impl MergedTile for Crystal {
                     init_active: compare_data.1,
                     ident: compare_data.0,
                     active: compare_data.1,
+                    surface: compare_data.2,
                 },
                 half_extent,
             },
             HurtMarker,
+            *surface,
         ));
     }
 
     fn compare_data(&self) -> Self::CompareData {
-        (self.ident, self.init_active)
+        (self.ident, self.init_active, self.surface)
     }
 }
 
@@ -322,6 +325,7 @@ impl From<IntGridCell> for Crystal {
             },
             active: init_active,
             init_active,
+            surface: cell.into(),
         }
     }
 }
@@ -329,7 +333,8 @@ impl From<IntGridCell> for Crystal {
 /// The horizontal offset between active crystals and inactive crystals in the crystal tilemap
 const CRYSTAL_INDEX_OFFSET: u32 = 5;
 
-fn toggle_crystal_group(
+/// Toggles a [`CrystalGroup`], adding or removing its collider.
+pub fn toggle_crystal_group(
     commands: &mut Commands,
     crystal_group_entity: Entity,
     crystal_group: &mut CrystalGroup,
@@ -349,7 +354,8 @@ fn toggle_crystal_group(
     }
 }
 
-fn toggle_crystal(crystal: &mut Crystal, crystal_index: &mut TileTextureIndex) {
+/// Toggles a single [`Crystal`] tile between its active and inactive sprite.
+pub fn toggle_crystal(crystal: &mut Crystal, crystal_index: &mut TileTextureIndex) {
     if !crystal.active {
         crystal.active = true;
         crystal_index.0 -= CRYSTAL_INDEX_OFFSET;
@@ -359,27 +365,6 @@ fn toggle_crystal(crystal: &mut Crystal, crystal_index: &mut TileTextureIndex) {
     }
 }
 
-/// [`System`] that listens to [`LevelSwitchEvent`]s to ensure that [`Crystal`] states are reset
-/// when switching between rooms.
-pub fn reset_crystals(
-    mut commands: Commands,
-    mut q_crystals: Query<(&mut Crystal, &mut TileTextureIndex)>,
-    mut q_crystal_groups: Query<(Entity, &mut CrystalGroup)>,
-) {
-    for (entity, mut crystal_group) in q_crystal_groups.iter_mut() {
-        let crystal = &crystal_group.representative;
-        if crystal.init_active != crystal.active {
-            toggle_crystal_group(&mut commands, entity, &mut crystal_group);
-        }
-    }
-
-    for (mut crystal, mut index) in q_crystals.iter_mut() {
-        if crystal.init_active != crystal.active {
-            toggle_crystal(&mut crystal, &mut index);
-        }
-    }
-}
-
 /// Event that will toggle all crystals of a certain color.
 #[derive(Event)]
 pub struct CrystalToggleEvent {
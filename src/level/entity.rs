@@ -3,6 +3,8 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::shared::GroupLabel;
+
+use super::surface::Surface;
 /// Component for things that hurt
 #[derive(Default, Component)]
 pub struct HurtMarker;
@@ -18,6 +20,8 @@ pub struct SpikeBundle {
     fixed_entity_bundle: FixedEntityBundle,
     hurt_marker: HurtMarker,
     spike: Spike,
+    #[from_int_grid_cell]
+    surface: Surface,
 }
 
 /// [`Bundle`] used to group together components commonly used together when initializing physics
@@ -40,6 +44,14 @@ impl From<&EntityInstance> for FixedEntityBundle {
                     GroupLabel::LIGHT_RAY | GroupLabel::WHITE_RAY | GroupLabel::BLUE_RAY,
                 ),
             },
+            "Checkpoint" => FixedEntityBundle {
+                collider: Collider::cuboid(4., 8.),
+                rigid_body: RigidBody::Fixed,
+                collision_groups: CollisionGroups::new(
+                    GroupLabel::CHECKPOINT,
+                    GroupLabel::PLAYER_SENSOR,
+                ),
+            },
             "CrystalShard" => FixedEntityBundle {
                 collider: Collider::cuboid(6., 6.),
                 rigid_body: RigidBody::Fixed,
@@ -0,0 +1,471 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{prelude::*, time::Stopwatch, utils::HashMap};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::config::Config;
+
+use super::{
+    crystal::{on_crystal_changed, CrystalColor, CrystalGroup, CrystalIdent, CrystalToggleEvent},
+    entity_level_iid,
+    platform::{ChangePlatformStateEvent, MovingPlatform, PlatformState},
+    sensor::{update_light_sensors, LightSensor},
+    wiring::{EntityIidCache, WireSignalEvent, WireTargets},
+    CurrentLevel, LevelSystems,
+};
+
+/// [`Plugin`] for [`LogicGate`]s, which combine the outputs of [`LightSensor`]s (and other gates)
+/// before toggling crystals and platforms.
+pub struct LogicGatePlugin;
+
+impl Plugin for LogicGatePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<LogicGateBundle>("LogicGate")
+            .add_systems(
+                FixedUpdate,
+                evaluate_logic_gates
+                    .after(update_light_sensors)
+                    .before(on_crystal_changed)
+                    .in_set(LevelSystems::Simulation),
+            )
+            .add_systems(Update, draw_logic_wires.run_if(logic_wires_enabled));
+    }
+}
+
+/// The operation a [`LogicGate`] performs on its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicGateKind {
+    And,
+    Or,
+    Xor,
+    /// On when none of the inputs are on
+    Not,
+    /// On when at least `count` of the inputs are on
+    Counter,
+    /// Repeats whether any input is on, `delay` later
+    Delay,
+}
+
+impl From<&String> for LogicGateKind {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "And" => LogicGateKind::And,
+            "Or" => LogicGateKind::Or,
+            "Xor" => LogicGateKind::Xor,
+            "Not" => LogicGateKind::Not,
+            "Counter" => LogicGateKind::Counter,
+            "Delay" => LogicGateKind::Delay,
+            _ => panic!("String {} does not represent a LogicGateKind", value),
+        }
+    }
+}
+
+/// [`Component`] spawned from the `LogicGate` Ldtk entity. Inputs are signal ids, driven by
+/// [`LightSensor::signal_id`] or by the `output_id` of another gate, plus any sensor or gate whose
+/// [`WireTargets`] reference this gate. Whenever the output of the gate changes, it toggles its
+/// crystals, plays/pauses its platforms and signals its own [`WireTargets`], exactly like a sensor
+/// would.
+#[derive(Component, Debug, Clone)]
+pub struct LogicGate {
+    pub kind: LogicGateKind,
+    /// Signal ids read by this gate
+    pub inputs: Vec<i32>,
+    /// Signal id written by this gate, or -1
+    pub output_id: i32,
+    /// The crystals to toggle when the output changes, if any
+    pub toggle_ident: Option<CrystalIdent>,
+    /// The id of the platform to toggle, or -1
+    pub platform_id: i32,
+    /// Number of inputs that need to be on for [`LogicGateKind::Counter`]
+    count: usize,
+    /// Delay used by [`LogicGateKind::Delay`]
+    delay: Duration,
+    /// Current output, `None` until the gate is first evaluated after a reset so that the
+    /// initial state of the gate does not toggle anything.
+    output: Option<bool>,
+    /// Pending output changes for [`LogicGateKind::Delay`], keyed by when they are due
+    pending: VecDeque<(Duration, bool)>,
+    /// Clock for [`LogicGateKind::Delay`]
+    clock: Stopwatch,
+}
+
+impl LogicGate {
+    /// Iterates over the value of every input, given the current value of every signal and of
+    /// every entity wired into this gate.
+    fn iter_inputs<'a>(
+        &'a self,
+        signals: &'a HashMap<i32, bool>,
+        wired: &'a [bool],
+    ) -> impl Iterator<Item = bool> + 'a {
+        self.inputs
+            .iter()
+            .map(|input| signals.get(input).copied().unwrap_or(false))
+            .chain(wired.iter().copied())
+    }
+
+    /// Computes the output of the gate given the current value of every signal and of every
+    /// entity wired into this gate.
+    fn evaluate(&self, signals: &HashMap<i32, bool>, wired: &[bool]) -> bool {
+        let mut on = self.iter_inputs(signals, wired);
+
+        match self.kind {
+            LogicGateKind::And => (!self.inputs.is_empty() || !wired.is_empty()) && on.all(|x| x),
+            LogicGateKind::Or => on.any(|x| x),
+            LogicGateKind::Xor => on.filter(|x| *x).count() % 2 == 1,
+            LogicGateKind::Not => !on.any(|x| x),
+            LogicGateKind::Counter => on.filter(|x| *x).count() >= self.count,
+            LogicGateKind::Delay => self.output.unwrap_or(false),
+        }
+    }
+
+    /// Feeds the current input of a [`LogicGateKind::Delay`] gate and returns the delayed output.
+    fn advance_delay(&mut self, input: bool, delta: Duration) -> bool {
+        self.clock.tick(delta);
+        let now = self.clock.elapsed();
+
+        let Some(cur) = self.output else {
+            return input;
+        };
+        let last_queued = self.pending.back().map(|(_, value)| *value).unwrap_or(cur);
+        if last_queued != input {
+            self.pending.push_back((now + self.delay, input));
+        }
+
+        let mut out = cur;
+        while let Some((due, value)) = self.pending.front().copied() {
+            if due > now {
+                break;
+            }
+            out = value;
+            self.pending.pop_front();
+        }
+        out
+    }
+
+    /// Puts the gate back in the state it was spawned in.
+    pub fn reset(&mut self) {
+        self.output = None;
+        self.pending.clear();
+        self.clock.reset();
+    }
+}
+
+impl From<&EntityInstance> for LogicGate {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let kind: LogicGateKind = entity_instance
+            .get_enum_field("gate")
+            .expect("gate needs to be an enum field on all logic gates")
+            .into();
+
+        let inputs = entity_instance
+            .get_maybe_ints_field("inputs")
+            .expect("inputs needs to be an int array field on all logic gates")
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+
+        let output_id = match entity_instance.get_int_field("output_id") {
+            Ok(output_id) => *output_id,
+            Err(_) => -1,
+        };
+
+        let toggle_ident = entity_instance
+            .get_enum_field("toggle_color")
+            .ok()
+            .map(|color| CrystalIdent {
+                color: CrystalColor::from(color),
+                id: entity_instance.get_int_field("id").copied().unwrap_or(0),
+            });
+
+        let platform_id = match entity_instance.get_int_field("platform_id") {
+            Ok(platform_id) => *platform_id,
+            Err(_) => -1,
+        };
+
+        let count = match entity_instance.get_int_field("count") {
+            Ok(count) => (*count).max(0) as usize,
+            Err(_) => 1,
+        };
+
+        let delay = match entity_instance.get_int_field("delay") {
+            Ok(millis) => Duration::from_millis((*millis).max(0) as u64),
+            Err(_) => Duration::ZERO,
+        };
+
+        LogicGate {
+            kind,
+            inputs,
+            output_id,
+            toggle_ident,
+            platform_id,
+            count,
+            delay,
+            output: None,
+            pending: VecDeque::new(),
+            clock: Stopwatch::new(),
+        }
+    }
+}
+
+/// [`Bundle`] registered with Ldtk to spawn [`LogicGate`]s.
+#[derive(Bundle, LdtkEntity)]
+pub struct LogicGateBundle {
+    #[from_entity_instance]
+    gate: LogicGate,
+    #[from_entity_instance]
+    targets: WireTargets,
+}
+
+/// [`System`] that runs on [`FixedUpdate`] after [`update_light_sensors`], evaluating each
+/// [`LogicGate`] in the current level and sending [`CrystalToggleEvent`]s and
+/// [`ChangePlatformStateEvent`]s when a gate's output changes.
+///
+/// Gates can feed into each other. Rather than sorting them, every gate is re-evaluated once per
+/// gate so that chains settle within a single tick. Entities wired into a gate through their
+/// [`WireTargets`] are read directly rather than through [`WireSignalEvent`]s for the same reason.
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_logic_gates(
+    q_sensors: Query<(Entity, &LightSensor, &WireTargets)>,
+    mut q_gates: Query<(Entity, &mut LogicGate, &WireTargets)>,
+    q_parents: Query<&Parent>,
+    q_level_iid: Query<&LevelIid>,
+    current_level: Res<CurrentLevel>,
+    entity_iid_cache: Res<EntityIidCache>,
+    mut ev_crystal_toggle: EventWriter<CrystalToggleEvent>,
+    mut ev_platform_change: EventWriter<ChangePlatformStateEvent>,
+    mut ev_wire_signal: EventWriter<WireSignalEvent>,
+    time: Res<Time>,
+) {
+    let in_current_level = |entity: Entity| {
+        entity_level_iid(entity, &q_parents, &q_level_iid)
+            .is_some_and(|level_iid| *level_iid == current_level.level_iid)
+    };
+
+    let mut signals: HashMap<i32, bool> = HashMap::new();
+    // value of every sensor and gate, keyed by entity, for gates wired through `WireTargets`
+    let mut entity_signals: HashMap<Entity, bool> = HashMap::new();
+    // sources wired into each gate
+    let mut wired_sources: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (entity, sensor, targets) in q_sensors.iter() {
+        if !in_current_level(entity) {
+            continue;
+        }
+        entity_signals.insert(entity, sensor.is_active);
+        for target in targets.resolve(&entity_iid_cache) {
+            wired_sources.entry(target).or_default().push(entity);
+        }
+        if sensor.signal_id >= 0 {
+            *signals.entry(sensor.signal_id).or_default() |= sensor.is_active;
+        }
+    }
+
+    let mut gates = q_gates
+        .iter_mut()
+        .filter(|(entity, _, _)| in_current_level(*entity))
+        .collect::<Vec<_>>();
+
+    for (entity, _, targets) in gates.iter() {
+        for target in targets.resolve(&entity_iid_cache) {
+            wired_sources.entry(target).or_default().push(*entity);
+        }
+    }
+
+    let wired_inputs = |entity: Entity, entity_signals: &HashMap<Entity, bool>| {
+        wired_sources
+            .get(&entity)
+            .map(|sources| {
+                sources
+                    .iter()
+                    .map(|source| entity_signals.get(source).copied().unwrap_or(false))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+
+    for _ in 0..gates.len() {
+        for (entity, gate, _) in gates.iter() {
+            let output = gate.evaluate(&signals, &wired_inputs(*entity, &entity_signals));
+            entity_signals.insert(*entity, output);
+            if gate.output_id >= 0 {
+                signals.insert(gate.output_id, output);
+            }
+        }
+    }
+
+    for (entity, gate, targets) in gates.iter_mut() {
+        let wired = wired_inputs(*entity, &entity_signals);
+        let mut new_output = gate.evaluate(&signals, &wired);
+        if gate.kind == LogicGateKind::Delay {
+            let input = gate.iter_inputs(&signals, &wired).any(|x| x);
+            new_output = gate.advance_delay(input, time.delta());
+        }
+
+        let old_output = gate.output.replace(new_output);
+        if old_output.is_none_or(|old_output| old_output == new_output) {
+            continue;
+        }
+
+        if let Some(toggle_ident) = gate.toggle_ident {
+            ev_crystal_toggle.send(CrystalToggleEvent {
+                color: toggle_ident,
+            });
+        }
+        if gate.platform_id >= 0 {
+            ev_platform_change.send(ChangePlatformStateEvent {
+                id: gate.platform_id,
+                new_state: if new_output {
+                    PlatformState::Play
+                } else {
+                    PlatformState::Pause
+                },
+            });
+        }
+        targets.send(new_output, &entity_iid_cache, &mut ev_wire_signal);
+    }
+}
+
+fn logic_wires_enabled(config: Res<Config>) -> bool {
+    config.debug_config.logic_wires
+}
+
+/// Debug [`System`] that draws the connections between [`LightSensor`]s, [`LogicGate`]s and the
+/// crystals, platforms and other entities they drive. Enabled with `logic_wires` in the debug
+/// config.
+pub fn draw_logic_wires(
+    q_sensors: Query<(&LightSensor, &GlobalTransform, &WireTargets)>,
+    q_gates: Query<(&LogicGate, &GlobalTransform, &WireTargets)>,
+    q_transforms: Query<&GlobalTransform>,
+    entity_iid_cache: Res<EntityIidCache>,
+    q_crystal_groups: Query<(&CrystalGroup, &GlobalTransform)>,
+    q_platforms: Query<(&MovingPlatform, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    const ON_COLOR: Color = Color::srgb(0.3, 1.0, 0.3);
+    const OFF_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+    let wire_color = |on: bool| if on { ON_COLOR } else { OFF_COLOR };
+
+    let mut draw_targets = |from: Vec2, targets: &WireTargets, on: bool| {
+        for target in targets.resolve(&entity_iid_cache) {
+            if let Ok(target_transform) = q_transforms.get(target) {
+                gizmos.line_2d(from, target_transform.translation().xy(), wire_color(on));
+            }
+        }
+    };
+    for (sensor, sensor_transform, targets) in q_sensors.iter() {
+        draw_targets(
+            sensor_transform.translation().xy(),
+            targets,
+            sensor.is_active,
+        );
+    }
+    for (gate, gate_transform, targets) in q_gates.iter() {
+        draw_targets(
+            gate_transform.translation().xy(),
+            targets,
+            gate.output.unwrap_or(false),
+        );
+    }
+
+    for (gate, gate_transform, _) in q_gates.iter() {
+        let gate_pos = gate_transform.translation().xy();
+        let gate_on = gate.output.unwrap_or(false);
+        gizmos.circle_2d(gate_pos, 4.0, wire_color(gate_on));
+
+        for input in gate.inputs.iter() {
+            for (sensor, sensor_transform, _) in q_sensors.iter() {
+                if sensor.signal_id == *input {
+                    gizmos.line_2d(
+                        sensor_transform.translation().xy(),
+                        gate_pos,
+                        wire_color(sensor.is_active),
+                    );
+                }
+            }
+            for (other, other_transform, _) in q_gates.iter() {
+                if other.output_id == *input {
+                    gizmos.line_2d(
+                        other_transform.translation().xy(),
+                        gate_pos,
+                        wire_color(other.output.unwrap_or(false)),
+                    );
+                }
+            }
+        }
+
+        if let Some(toggle_ident) = gate.toggle_ident {
+            for (crystal_group, crystal_transform) in q_crystal_groups.iter() {
+                if crystal_group.representative.ident == toggle_ident {
+                    gizmos.line_2d(
+                        gate_pos,
+                        crystal_transform.translation().xy(),
+                        wire_color(gate_on),
+                    );
+                }
+            }
+        }
+        for (platform, platform_transform) in q_platforms.iter() {
+            if gate.platform_id >= 0 && platform.id == gate.platform_id {
+                gizmos.line_2d(
+                    gate_pos,
+                    platform_transform.translation().xy(),
+                    wire_color(gate_on),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate(kind: LogicGateKind, inputs: Vec<i32>) -> LogicGate {
+        LogicGate {
+            kind,
+            inputs,
+            output_id: -1,
+            toggle_ident: None,
+            platform_id: -1,
+            count: 2,
+            delay: Duration::from_millis(100),
+            output: None,
+            pending: VecDeque::new(),
+            clock: Stopwatch::new(),
+        }
+    }
+
+    #[test]
+    fn combinational_gates() {
+        let signals: HashMap<i32, bool> = [(0, true), (1, false), (2, true)].into_iter().collect();
+        assert!(!gate(LogicGateKind::And, vec![0, 1]).evaluate(&signals, &[]));
+        assert!(gate(LogicGateKind::And, vec![0, 2]).evaluate(&signals, &[]));
+        assert!(gate(LogicGateKind::Or, vec![0, 1]).evaluate(&signals, &[]));
+        assert!(!gate(LogicGateKind::Xor, vec![0, 2]).evaluate(&signals, &[]));
+        assert!(gate(LogicGateKind::Not, vec![1]).evaluate(&signals, &[]));
+        assert!(gate(LogicGateKind::Counter, vec![0, 1, 2]).evaluate(&signals, &[]));
+        assert!(!gate(LogicGateKind::Counter, vec![0, 1]).evaluate(&signals, &[]));
+    }
+
+    #[test]
+    fn wired_inputs_count_as_inputs() {
+        let signals: HashMap<i32, bool> = [(0, true)].into_iter().collect();
+        assert!(gate(LogicGateKind::And, vec![]).evaluate(&signals, &[true, true]));
+        assert!(!gate(LogicGateKind::And, vec![0]).evaluate(&signals, &[false]));
+        assert!(!gate(LogicGateKind::And, vec![]).evaluate(&signals, &[]));
+        assert!(gate(LogicGateKind::Xor, vec![]).evaluate(&signals, &[false, true]));
+    }
+
+    #[test]
+    fn delay_gate_repeats_input_later() {
+        let tick = Duration::from_millis(50);
+        let mut g = gate(LogicGateKind::Delay, vec![0]);
+        g.output = Some(g.advance_delay(false, tick));
+        g.output = Some(g.advance_delay(true, tick));
+        assert_eq!(g.output, Some(false));
+        g.output = Some(g.advance_delay(true, tick));
+        assert_eq!(g.output, Some(false));
+        g.output = Some(g.advance_delay(true, tick));
+        assert_eq!(g.output, Some(true));
+    }
+}
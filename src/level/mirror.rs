@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
-use super::entity::FixedEntityBundle;
+use super::{entity::FixedEntityBundle, surface::Surface};
 
 pub struct MirrorPlugin;
 impl Plugin for MirrorPlugin {
@@ -19,4 +19,6 @@ pub struct MirrorBundle {
     #[from_int_grid_cell]
     fixed_entity_bundle: FixedEntityBundle,
     mirror: Mirror,
+    #[from_int_grid_cell]
+    surface: Surface,
 }
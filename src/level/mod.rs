@@ -2,17 +2,24 @@ use std::time::Duration;
 
 use bevy::{ecs::system::SystemId, prelude::*};
 use bevy_ecs_ldtk::{ldtk::Level, prelude::*, systems::process_ldtk_levels, LevelIid};
-use cruciera::CrucieraPlugin;
+use checkpoint::CheckpointPlugin;
 use decoration::DecorationPlugin;
 use egg::EggPlugin;
 use enum_map::{enum_map, EnumMap};
 use level_completion::LevelCompletionPlugin;
+use logic::LogicGatePlugin;
 use merge_tile::spawn_merged_tiles;
 use mirror::MirrorPlugin;
+use npc::NpcPlugin;
+use rewind::RewindPlugin;
 use semisolid::SemiSolidPlugin;
 use sensor::LightSensorPlugin;
 use shard::CrystalShardPlugin;
+use snapshot::LevelSnapshotPlugin;
+use solution::SolutionHintPlugin;
 use speedrun::SpeedrunTimerPlugin;
+use surface::SurfacePlugin;
+use trigger::TriggerPlugin;
 
 use crate::{
     camera::{
@@ -30,23 +37,32 @@ use platform::PlatformPlugin;
 use setup::LevelSetupPlugin;
 use start_flag::{init_start_marker, StartFlagBundle};
 use walls::{Wall, WallBundle};
+use wiring::WiringPlugin;
 
-mod cruciera;
+pub mod checkpoint;
 pub mod crystal;
 mod decoration;
 mod egg;
 pub mod entity;
 mod level_completion;
+pub mod logic;
 mod merge_tile;
 pub mod mirror;
+mod npc;
 pub mod platform;
+pub mod rewind;
 mod semisolid;
 pub mod sensor;
 mod setup;
 pub mod shard;
+pub mod snapshot;
+pub mod solution;
 pub mod speedrun;
 pub mod start_flag;
+pub mod surface;
+mod trigger;
 mod walls;
+pub mod wiring;
 
 /// [`Plugin`] that handles everything related to the level.
 pub struct LevelManagementPlugin;
@@ -59,13 +75,21 @@ impl Plugin for LevelManagementPlugin {
             .add_plugins(PlatformPlugin)
             .add_plugins(CrystalShardPlugin)
             .add_plugins(LightSensorPlugin)
+            .add_plugins(LogicGatePlugin)
+            .add_plugins(WiringPlugin)
             .add_plugins(SemiSolidPlugin)
+            .add_plugins(SurfacePlugin)
             .add_plugins(MirrorPlugin)
             .add_plugins(EggPlugin)
             .add_plugins(LevelCompletionPlugin)
             .add_plugins(DecorationPlugin)
-            .add_plugins(CrucieraPlugin)
+            .add_plugins(NpcPlugin)
+            .add_plugins(TriggerPlugin)
             .add_plugins(SpeedrunTimerPlugin)
+            .add_plugins(CheckpointPlugin)
+            .add_plugins(LevelSnapshotPlugin)
+            .add_plugins(RewindPlugin)
+            .add_plugins(SolutionHintPlugin)
             .init_resource::<CurrentLevel>()
             .register_ldtk_entity::<LdtkPlayerBundle>("Lyra")
             .register_ldtk_entity::<StartFlagBundle>("Start")
@@ -102,23 +126,13 @@ impl Plugin for LevelManagementPlugin {
             )
             .configure_sets(
                 Update,
-                LevelSystems::Simulation.run_if(
-                    in_state(GameState::Playing)
-                        .or(in_state(AnimationState::Shard)) // FIXME: skull emoji
-                        .or(in_state(AnimationState::ShardDialogue))
-                        .or(in_state(AnimationState::Cruciera))
-                        .or(in_state(AnimationState::CrucieraDialogue)),
-                ),
+                LevelSystems::Simulation
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::Cutscene))),
             )
             .configure_sets(
                 FixedUpdate,
-                LevelSystems::Simulation.run_if(
-                    in_state(GameState::Playing)
-                        .or(in_state(AnimationState::Shard)) // FIXME: skull emoji
-                        .or(in_state(AnimationState::ShardDialogue))
-                        .or(in_state(AnimationState::Cruciera))
-                        .or(in_state(AnimationState::CrucieraDialogue)),
-                ),
+                LevelSystems::Simulation
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::Cutscene))),
             );
     }
 }
@@ -129,6 +143,16 @@ pub struct CurrentLevel {
     pub level_iid: LevelIid,
     pub level_box: Rect,
     pub allowed_colors: EnumMap<LightColor, bool>,
+    /// Number of beams of each color the player can shoot per life. Listing a color several times
+    /// in the `AllowedColors` level field gives it that many beams.
+    pub beam_counts: EnumMap<LightColor, u32>,
+    /// Colors whose segments are one-way platforms in this level, from the optional `SolidBeams`
+    /// enum array level field
+    pub solid_colors: EnumMap<LightColor, bool>,
+    /// Whether beams can be recalled in this level, from the optional `AllowRecall` level field
+    pub allow_recall: bool,
+    /// Track set by a trigger, played instead of the level's own until the level changes
+    pub bgm: Option<BgmTrack>,
 }
 
 /// [`SystemSet`] used to distinguish different types of systems
@@ -152,6 +176,32 @@ pub fn get_ldtk_level_data<'ldtk>(
     Ok(&ldtk_project.json_data().levels)
 }
 
+/// Walks up the hierarchy of an Ldtk entity to find the [`LevelIid`] of the level it was spawned
+/// in.
+pub fn entity_level_iid<'a>(
+    entity: Entity,
+    q_parents: &Query<&Parent>,
+    q_level_iid: &'a Query<&LevelIid>,
+) -> Option<&'a LevelIid> {
+    let mut cur = entity;
+    while let Ok(parent) = q_parents.get(cur) {
+        cur = parent.get();
+        if let Ok(level_iid) = q_level_iid.get(cur) {
+            return Some(level_iid);
+        }
+    }
+    None
+}
+
+/// The world position of an Ldtk entity's pivot, which is where the player spawns when the entity
+/// is a start flag or checkpoint.
+pub fn entity_world_pos(instance: &EntityInstance) -> Vec2 {
+    Vec2::new(
+        instance.world_x.expect("Lightborne uses Free world layout") as f32,
+        -instance.world_y.expect("Lightborne uses Free world layout") as f32,
+    )
+}
+
 pub fn level_box_from_level(level: &Level) -> Rect {
     Rect::new(
         level.world_x as f32,
@@ -221,11 +271,28 @@ pub fn switch_level(
                 let allowed_colors_map = enum_map! {
                     val => allowed_colors.contains(&val),
                 };
+                let beam_counts = enum_map! {
+                    val => allowed_colors.iter().filter(|color| **color == val).count() as u32,
+                };
+
+                let solid_beams: Vec<LightColor> = match level.iter_enums_field("SolidBeams") {
+                    Ok(colors) => colors.map(|color_str| color_str.into()).collect(),
+                    Err(_) => vec![],
+                };
+                let solid_colors = enum_map! {
+                    val => solid_beams.contains(&val),
+                };
+
+                let allow_recall = level.get_bool_field("AllowRecall").copied().unwrap_or(true);
 
                 *current_level = CurrentLevel {
                     level_iid: LevelIid::new(level.iid.clone()),
                     level_box,
                     allowed_colors: allowed_colors_map,
+                    beam_counts,
+                    solid_colors,
+                    allow_recall,
+                    bgm: None,
                 };
                 *level_selection = LevelSelection::iid(current_level.level_iid.clone());
             }
@@ -257,6 +324,10 @@ pub fn set_bgm_from_current_level(
     ldtk_projects: Query<&LdtkProjectHandle>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
 ) {
+    if let Some(track) = current_level.bgm {
+        ev_change_bgm.send(ChangeBgmEvent(track));
+        return;
+    }
     let Ok(ldtk_handle) = ldtk_projects.get_single() else {
         return;
     };
@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::AnimationConfig,
+    camera::{HIGHRES_LAYER, TERRAIN_LAYER},
+    config::Config,
+    cutscene::StartCutsceneEvent,
+    lighting::LineLight2d,
+    player::PlayerMarker,
+    save,
+    shared::GameState,
+};
+
+use super::LevelSystems;
+
+/// Cutscene played by npcs that don't set one, which zooms in on Lyra and plays their dialogue.
+const DEFAULT_NPC_CUTSCENE: &str = "npc";
+
+/// Name the [`NpcsTalkedTo`] are saved under.
+const NPCS_TALKED_TO_SAVE: &str = "npcs";
+
+/// [`Plugin`] for the characters placed in levels with the `Npc` Ldtk entity. Cruciera's `Gala`
+/// entity is an npc too, with her sprite and dialogue as defaults.
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<LdtkNpcBundle>("Npc")
+            .register_ldtk_entity::<LdtkCrucieraBundle>("Gala")
+            .insert_resource(save::load::<NpcsTalkedTo>(NPCS_TALKED_TO_SAVE))
+            .add_systems(PreUpdate, setup_npcs.in_set(LevelSystems::Processing))
+            .add_systems(Update, reset_npcs.in_set(LevelSystems::Reset))
+            .add_systems(OnExit(GameState::Cutscene), stop_npc_talking)
+            .add_systems(
+                Update,
+                (
+                    check_talk_to_npc,
+                    save::persist_resource::<NpcsTalkedTo>(NPCS_TALKED_TO_SAVE),
+                )
+                    .chain()
+                    .in_set(LevelSystems::Simulation),
+            );
+    }
+}
+
+/// How an [`Npc`] starts talking to Lyra once she is within its interaction radius.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NpcTrigger {
+    /// As soon as she comes close, once per level attempt
+    Auto,
+    /// When the interact key is pressed, as many times as the player wants
+    Interact,
+}
+
+impl From<&String> for NpcTrigger {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "Auto" => NpcTrigger::Auto,
+            "Interact" => NpcTrigger::Interact,
+            _ => panic!("String {} does not represent an NpcTrigger", value),
+        }
+    }
+}
+
+/// [`Component`] for a character that plays a cutscene (usually just its dialogue) when Lyra talks
+/// to it. The sprite sheet is a single row of frames.
+#[derive(Component, Debug)]
+pub struct Npc {
+    /// Iid of the Ldtk entity, used to remember the npcs played once per save
+    iid: String,
+    sprite_sheet: String,
+    /// Size of the sprite sheet frames, which is the size of the Ldtk entity
+    frame_size: UVec2,
+    /// First and last frame of the animation played while idle
+    idle_frames: (usize, usize),
+    /// First and last frame of the animation played while its cutscene runs
+    talk_frames: (usize, usize),
+    fps: u8,
+    interaction_radius: f32,
+    trigger: NpcTrigger,
+    /// Dialogue played by the dialogue steps of the cutscene
+    dialogue: Option<String>,
+    cutscene: String,
+    /// Whether the npc only talks once for good (see [`NpcsTalkedTo`]), instead of once per
+    /// attempt
+    once_per_save: bool,
+    /// Color of the point light around the npc, if any
+    light: Option<Vec4>,
+    talked: bool,
+    talking: bool,
+}
+
+impl Npc {
+    /// Reads the optional fields of an npc's Ldtk entity, using `defaults` for the missing ones.
+    fn with_fields(defaults: Npc, entity_instance: &EntityInstance) -> Self {
+        let mut npc = Npc {
+            iid: entity_instance.iid.clone(),
+            frame_size: UVec2::new(
+                entity_instance.width.max(1) as u32,
+                entity_instance.height.max(1) as u32,
+            ),
+            ..defaults
+        };
+        if let Ok(sprite_sheet) = entity_instance.get_string_field("sprite_sheet") {
+            npc.sprite_sheet = sprite_sheet.clone();
+        }
+        let frame = |field| match entity_instance.get_int_field(field) {
+            Ok(frame) => Some(*frame as usize),
+            Err(_) => None,
+        };
+        npc.idle_frames = (
+            frame("idle_first_frame").unwrap_or(npc.idle_frames.0),
+            frame("idle_last_frame").unwrap_or(npc.idle_frames.1),
+        );
+        npc.talk_frames = match (frame("talk_first_frame"), frame("talk_last_frame")) {
+            (Some(first), Some(last)) => (first, last),
+            _ => npc.idle_frames,
+        };
+        if let Ok(fps) = entity_instance.get_int_field("fps") {
+            npc.fps = (*fps).clamp(1, u8::MAX as i32) as u8;
+        }
+        if let Ok(radius) = entity_instance.get_float_field("interaction_radius") {
+            npc.interaction_radius = *radius;
+        }
+        if let Ok(trigger) = entity_instance.get_enum_field("trigger") {
+            npc.trigger = trigger.into();
+        }
+        if let Ok(Some(dialogue)) = entity_instance.get_maybe_string_field("dialogue") {
+            npc.dialogue = Some(dialogue.clone());
+        }
+        if let Ok(Some(cutscene)) = entity_instance.get_maybe_string_field("cutscene") {
+            npc.cutscene = cutscene.clone();
+        }
+        if let Ok(once_per_save) = entity_instance.get_bool_field("once_per_save") {
+            npc.once_per_save = *once_per_save;
+        }
+        if let Ok(light) = entity_instance.get_color_field("light") {
+            npc.light = Some(light.to_srgba().with_alpha(0.8).to_vec4());
+        }
+        npc
+    }
+}
+
+impl From<&EntityInstance> for Npc {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let sprite_sheet = entity_instance
+            .get_string_field("sprite_sheet")
+            .expect("sprite_sheet needs to be a string field on all npcs");
+        let defaults = Npc {
+            iid: String::new(),
+            sprite_sheet: sprite_sheet.clone(),
+            frame_size: UVec2::ZERO,
+            idle_frames: (0, 0),
+            talk_frames: (0, 0),
+            fps: 5,
+            interaction_radius: 40.,
+            trigger: NpcTrigger::Interact,
+            dialogue: None,
+            cutscene: DEFAULT_NPC_CUTSCENE.to_string(),
+            once_per_save: false,
+            light: None,
+            talked: false,
+            talking: false,
+        };
+        Npc::with_fields(defaults, entity_instance)
+    }
+}
+
+/// Builds the [`Npc`] of Cruciera's `Gala` entity.
+fn cruciera(entity_instance: &EntityInstance) -> Npc {
+    let defaults = Npc {
+        iid: String::new(),
+        sprite_sheet: "gala_sheet.png".to_string(),
+        frame_size: UVec2::ZERO,
+        idle_frames: (0, 2),
+        talk_frames: (0, 2),
+        fps: 5,
+        interaction_radius: 40.,
+        trigger: NpcTrigger::Auto,
+        dialogue: Some("cruciera_intro".to_string()),
+        cutscene: DEFAULT_NPC_CUTSCENE.to_string(),
+        once_per_save: false,
+        light: Some(Vec4::new(1.0, 0.2, 0.2, 0.8)),
+        talked: false,
+        talking: false,
+    };
+    Npc::with_fields(defaults, entity_instance)
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct LdtkNpcBundle {
+    #[from_entity_instance]
+    npc: Npc,
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct LdtkCrucieraBundle {
+    #[with(cruciera)]
+    npc: Npc,
+}
+
+/// [`Resource`] holding the iids of the `once_per_save` npcs that have already talked to Lyra,
+/// saved under [`NPCS_TALKED_TO_SAVE`] so they stay quiet after the game is restarted.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct NpcsTalkedTo {
+    talked_to: HashSet<String>,
+}
+
+pub fn setup_npcs(
+    mut commands: Commands,
+    q_added_npcs: Query<(Entity, &Npc), Added<Npc>>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    for (entity, npc) in q_added_npcs.iter() {
+        let frames = npc.idle_frames.1.max(npc.talk_frames.1) + 1;
+        let texture_atlas_layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+            npc.frame_size,
+            frames as u32,
+            1,
+            None,
+            None,
+        ));
+
+        // insert sprite here because it depends on texture atlas which needs a resource
+        commands.entity(entity).insert((
+            Sprite {
+                image: asset_server.load(&npc.sprite_sheet),
+                texture_atlas: Some(TextureAtlas {
+                    layout: texture_atlas_layout,
+                    index: npc.idle_frames.0,
+                }),
+                ..default()
+            },
+            AnimationConfig::new(npc.idle_frames.0, npc.idle_frames.1, npc.fps, true),
+            HIGHRES_LAYER,
+        ));
+        if let Some(light) = npc.light {
+            commands
+                .entity(entity)
+                .with_child((LineLight2d::point(light, 40., 0.01), TERRAIN_LAYER));
+        }
+    }
+}
+
+/// [`System`] that starts the cutscene of the [`Npc`] Lyra is close to, when its trigger allows.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn check_talk_to_npc(
+    mut commands: Commands,
+    mut q_npcs: Query<(Entity, &GlobalTransform, &mut Npc)>,
+    q_lyra: Query<&GlobalTransform, (With<PlayerMarker>, Without<Npc>)>,
+    mut ev_start_cutscene: EventWriter<StartCutsceneEvent>,
+    mut talked_to: ResMut<NpcsTalkedTo>,
+    cur_game_state: Res<State<GameState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Res<Config>,
+) {
+    if *cur_game_state.get() != GameState::Playing {
+        return;
+    }
+    let Ok(lyra_transform) = q_lyra.get_single() else {
+        return;
+    };
+    let interact_pressed = keys.just_pressed(config.controls_config.key_interact);
+
+    for (entity, npc_transform, mut npc) in q_npcs.iter_mut() {
+        if npc_transform
+            .translation()
+            .distance(lyra_transform.translation())
+            >= npc.interaction_radius
+        {
+            continue;
+        }
+        let triggered = match npc.trigger {
+            NpcTrigger::Auto => !npc.talked,
+            NpcTrigger::Interact => interact_pressed,
+        };
+        if !triggered || (npc.once_per_save && talked_to.talked_to.contains(&npc.iid)) {
+            continue;
+        }
+
+        npc.talked = true;
+        npc.talking = true;
+        if npc.once_per_save {
+            talked_to.talked_to.insert(npc.iid.clone());
+        }
+        commands.entity(entity).insert(AnimationConfig::new(
+            npc.talk_frames.0,
+            npc.talk_frames.1,
+            npc.fps,
+            true,
+        ));
+        ev_start_cutscene.send(StartCutsceneEvent {
+            id: npc.cutscene.clone(),
+            dialogue: npc.dialogue.clone(),
+        });
+        return;
+    }
+}
+
+/// [`System`] that puts the [`Npc`]s back to their idle animation once their cutscene is over.
+pub fn stop_npc_talking(mut commands: Commands, mut q_npcs: Query<(Entity, &mut Npc)>) {
+    for (entity, mut npc) in q_npcs.iter_mut() {
+        if !npc.talking {
+            continue;
+        }
+        npc.talking = false;
+        commands.entity(entity).insert(AnimationConfig::new(
+            npc.idle_frames.0,
+            npc.idle_frames.1,
+            npc.fps,
+            true,
+        ));
+    }
+}
+
+pub fn reset_npcs(mut q_npcs: Query<&mut Npc>) {
+    for mut npc in q_npcs.iter_mut() {
+        npc.talked = false;
+    }
+}
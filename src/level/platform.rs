@@ -12,7 +12,7 @@ use crate::{
     shared::{GroupLabel, ResetLevel},
 };
 
-use super::{CurrentLevel, LevelSystems};
+use super::{surface::Surface, wiring::WireSignalEvent, CurrentLevel, LevelSystems};
 
 const PLAYER_WIDTH: f32 = 16.0;
 const PLAYER_HEIGHT: f32 = 19.0;
@@ -35,10 +35,15 @@ impl Plugin for PlatformPlugin {
                 .in_set(LevelSystems::Simulation)
                 .run_if(on_event::<ChangePlatformStateEvent>),
         )
+        .add_systems(
+            Update,
+            apply_wire_signals_to_platforms
+                .in_set(LevelSystems::Simulation)
+                .run_if(on_event::<WireSignalEvent>),
+        )
         .add_event::<ChangePlatformStateEvent>()
         .add_systems(FixedUpdate, move_platforms.in_set(LevelSystems::Simulation))
-        .register_ldtk_entity::<MovingPlatformBundle>("MovingPlatform")
-        .add_systems(FixedUpdate, reset_platforms.run_if(on_event::<ResetLevel>));
+        .register_ldtk_entity::<MovingPlatformBundle>("MovingPlatform");
     }
 }
 
@@ -71,7 +76,7 @@ impl From<&String> for PlatformState {
 }
 
 /// Component to represent a moving platforms
-#[derive(Default, Component)]
+#[derive(Default, Component, Clone, Debug)]
 pub struct MovingPlatform {
     pub path: Vec<IVec2>, // Array of points that the platform will traverse
     pub path_curve_points: Vec<bool>, // Array of booleans determining circular motion of platform
@@ -92,6 +97,46 @@ pub struct MovingPlatform {
 }
 
 impl MovingPlatform {
+    /// Puts the platform back at the start of its path, returning the translation it was spawned
+    /// at.
+    pub fn reset(&mut self) -> Vec3 {
+        self.curr_segment = match self.path.len() {
+            1 => self.path[0],
+            _ => self.path[1],
+        };
+        self.previous_segment = self.path[0];
+        self.curr_segment_index = 1;
+        self.curr_state = self.initial_state;
+        self.arc_time = 0.0;
+        self.current_position = Vec2::new(self.path[0].x as f32, self.path[0].y as f32);
+        Vec3::new(
+            (self.path[0].x as f32 * BLOCK_WIDTH) + (self.width as f32 / 2.0),
+            (22.0 * BLOCK_WIDTH) - (self.path[0].y as f32 * BLOCK_WIDTH)
+                + (self.height as f32 / 2.0),
+            0.0,
+        )
+    }
+
+    /// Moves the platform into `new_state`, respecting `can_reactivate` when leaving a
+    /// [`PlatformState::Stop`].
+    pub fn transition(&mut self, new_state: PlatformState) {
+        self.curr_state = match (self.curr_state, new_state) {
+            (PlatformState::Stop, PlatformState::Play) => {
+                if !self.can_reactivate && self.has_activated {
+                    PlatformState::Stop
+                } else {
+                    PlatformState::Play
+                }
+            }
+            (PlatformState::Stop, _) => PlatformState::Stop,
+            (PlatformState::Play, PlatformState::Stop) => {
+                self.has_activated = true;
+                PlatformState::Stop
+            }
+            (_, new_state) => new_state,
+        };
+    }
+
     fn get_next_direction_vec(&mut self, time: &Res<Time>) -> Vec2 {
         match self.path_curve_points[self.curr_segment_index as usize] {
             false => Vec2::new(
@@ -370,6 +415,12 @@ pub struct MovingPlatformBundle {
     #[sprite_sheet]
     pub sprite: Sprite,
     pub physics: PlatformPhysicsBundle,
+    #[with(platform_surface)]
+    pub surface: Surface,
+}
+
+fn platform_surface(_: &EntityInstance) -> Surface {
+    Surface::Platform
 }
 
 /// [System] that moves platforms during each [Update] step
@@ -539,27 +590,6 @@ pub fn move_platforms(
     }
 }
 
-/// [System] that resets the state of all platforms
-pub fn reset_platforms(mut platform_q: Query<(&mut MovingPlatform, &mut Transform)>) {
-    for (mut platform, mut transform) in platform_q.iter_mut() {
-        transform.translation = Vec3::new(
-            (platform.path[0].x as f32 * BLOCK_WIDTH) + (platform.width as f32 / 2.0),
-            (22.0 * BLOCK_WIDTH) - (platform.path[0].y as f32 * BLOCK_WIDTH)
-                + (platform.height as f32 / 2.0),
-            0.0,
-        );
-        platform.curr_segment = match platform.path.len() {
-            1 => platform.path[0],
-            _ => platform.path[1],
-        };
-        platform.previous_segment = platform.path[0];
-        platform.curr_segment_index = 1;
-        platform.curr_state = platform.initial_state;
-        platform.arc_time = 0.0;
-        platform.current_position = Vec2::new(platform.path[0].x as f32, platform.path[0].y as f32);
-    }
-}
-
 /// function that casts a ray shape relative to the player
 #[allow(clippy::too_many_arguments)]
 pub fn cast_player_ray_shape(
@@ -626,17 +656,7 @@ pub fn change_platform_state(
                         && current_level.level_iid == *levels.get(new_entity).unwrap()
                     {
                         //println!("Platform in level");
-                        platform.curr_state = match platform.curr_state {
-                            PlatformState::Play => PlatformState::Play,
-                            PlatformState::Pause => PlatformState::Play,
-                            PlatformState::Stop => {
-                                if !platform.can_reactivate && platform.has_activated {
-                                    PlatformState::Stop
-                                } else {
-                                    PlatformState::Play
-                                }
-                            }
-                        };
+                        platform.transition(PlatformState::Play);
                     }
                 }
             }
@@ -652,11 +672,7 @@ pub fn change_platform_state(
                     if platform.id == event.id
                         && current_level.level_iid == *levels.get(new_entity).unwrap()
                     {
-                        platform.curr_state = match platform.curr_state {
-                            PlatformState::Play => PlatformState::Pause,
-                            PlatformState::Pause => PlatformState::Pause,
-                            PlatformState::Stop => PlatformState::Stop,
-                        };
+                        platform.transition(PlatformState::Pause);
                     }
                 }
             }
@@ -672,17 +688,28 @@ pub fn change_platform_state(
                     if platform.id == event.id
                         && current_level.level_iid == *levels.get(entity).unwrap()
                     {
-                        platform.curr_state = match platform.curr_state {
-                            PlatformState::Play => {
-                                platform.has_activated = true;
-                                PlatformState::Stop
-                            }
-                            PlatformState::Pause => PlatformState::Stop,
-                            PlatformState::Stop => PlatformState::Stop,
-                        };
+                        platform.transition(PlatformState::Stop);
                     }
                 }
             }
         }
     }
 }
+
+/// [`System`] that plays a [`MovingPlatform`] when a wire targeting it turns on and pauses it when
+/// the wire turns off.
+pub fn apply_wire_signals_to_platforms(
+    mut ev_wire_signal: EventReader<WireSignalEvent>,
+    mut q_platforms: Query<&mut MovingPlatform>,
+) {
+    for ev in ev_wire_signal.read() {
+        let Ok(mut platform) = q_platforms.get_mut(ev.target) else {
+            continue;
+        };
+        platform.transition(if ev.active {
+            PlatformState::Play
+        } else {
+            PlatformState::Pause
+        });
+    }
+}
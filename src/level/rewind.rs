@@ -0,0 +1,180 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    config::Config,
+    light::LightSourceZMarker,
+    player::{animation::PlayerAnimationType, movement::PlayerMovement, PlayerMarker},
+};
+
+use super::{
+    checkpoint::ActiveCheckpoint,
+    platform::MovingPlatform,
+    snapshot::{LevelMotion, LevelSnapshot, LevelState},
+    LevelSystems,
+};
+
+/// How far back the player can rewind.
+const REWIND_DURATION: Duration = Duration::from_secs(5);
+
+/// Number of ticks between two frames holding a full [`LevelSnapshot`]. The other frames only
+/// hold the [`LevelMotion`] and the player, so sensors, crystals and the like rewind in steps of
+/// this many ticks.
+const KEYFRAME_INTERVAL: u32 = 10;
+
+/// [`Plugin`] for rewinding time, which lets the player hold a key to play the last few seconds of
+/// the level backwards. Enabled with the rewind assist setting.
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rewind>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    record_rewind_frame.run_if(not(rewind_key_pressed)),
+                    rewind_level.run_if(rewind_key_pressed),
+                )
+                    .run_if(rewind_enabled)
+                    .after(PhysicsSet::Writeback)
+                    .in_set(LevelSystems::Simulation),
+            )
+            .add_systems(Update, clear_rewind_history.in_set(LevelSystems::Reset));
+    }
+}
+
+/// The state of the level and of the player at one [`FixedUpdate`] tick.
+struct RewindFrame {
+    /// The full state of the level, only recorded every [`KEYFRAME_INTERVAL`] ticks
+    keyframe: Option<LevelSnapshot>,
+    motion: LevelMotion,
+    /// The checkpoint reached at that tick, which isn't part of the [`LevelSnapshot`] since it
+    /// holds one itself
+    checkpoint: ActiveCheckpoint,
+    player_translation: Vec3,
+    player_movement: PlayerMovement,
+    player_animation: PlayerAnimationType,
+}
+
+/// [`Resource`] holding a [`RewindFrame`] for every tick of the last [`REWIND_DURATION`], most
+/// recent last.
+#[derive(Resource, Default)]
+pub struct Rewind {
+    enabled: bool,
+    frames: VecDeque<RewindFrame>,
+    /// Ticks recorded since the last keyframe
+    since_keyframe: u32,
+}
+
+impl Rewind {
+    /// Turns rewinding on or off, forgetting everything recorded so far.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.clear();
+    }
+
+    fn clear(&mut self) {
+        self.frames.clear();
+        self.since_keyframe = 0;
+    }
+}
+
+fn rewind_enabled(rewind: Res<Rewind>) -> bool {
+    rewind.enabled
+}
+
+fn rewind_key_pressed(keys: Res<ButtonInput<KeyCode>>, config: Res<Config>) -> bool {
+    keys.pressed(config.controls_config.key_rewind)
+}
+
+/// [`System`] that records the current [`RewindFrame`], dropping the ones older than
+/// [`REWIND_DURATION`].
+#[allow(clippy::type_complexity)]
+pub fn record_rewind_frame(
+    level_state: LevelState,
+    // the filters keep this disjoint from the platform and light source queries of LevelState
+    q_player: Query<
+        (&Transform, &PlayerMovement, &PlayerAnimationType),
+        (
+            With<PlayerMarker>,
+            Without<MovingPlatform>,
+            Without<LightSourceZMarker>,
+        ),
+    >,
+    active_checkpoint: Res<ActiveCheckpoint>,
+    time: Res<Time<Fixed>>,
+    mut rewind: ResMut<Rewind>,
+) {
+    let Ok((player_transform, movement, animation)) = q_player.get_single() else {
+        return;
+    };
+    let capacity = (REWIND_DURATION.as_secs_f32() / time.timestep().as_secs_f32()) as usize;
+    while rewind.frames.len() >= capacity {
+        rewind.frames.pop_front();
+    }
+    let keyframe = (rewind.since_keyframe == 0).then(|| level_state.snapshot());
+    rewind.since_keyframe = (rewind.since_keyframe + 1) % KEYFRAME_INTERVAL;
+    rewind.frames.push_back(RewindFrame {
+        keyframe,
+        motion: level_state.motion(),
+        checkpoint: active_checkpoint.clone(),
+        player_translation: player_transform.translation,
+        player_movement: movement.clone(),
+        player_animation: *animation,
+    });
+}
+
+/// [`System`] that steps the level and the player one tick back in time. Runs after the physics
+/// step, so whatever the simulation did this tick is overwritten by the recorded frame.
+#[allow(clippy::type_complexity)]
+pub fn rewind_level(
+    mut level_state: LevelState,
+    mut q_player: Query<
+        (
+            &mut Transform,
+            &mut PlayerMovement,
+            &mut PlayerAnimationType,
+            &mut KinematicCharacterController,
+        ),
+        (
+            With<PlayerMarker>,
+            Without<MovingPlatform>,
+            Without<LightSourceZMarker>,
+        ),
+    >,
+    mut active_checkpoint: ResMut<ActiveCheckpoint>,
+    mut rewind: ResMut<Rewind>,
+) {
+    let Ok((mut player_transform, mut movement, mut animation, mut controller)) =
+        q_player.get_single_mut()
+    else {
+        return;
+    };
+    // the oldest frame is kept so that holding the key past it stays there
+    if rewind.frames.len() > 1 {
+        rewind.frames.pop_back();
+    }
+    let Some(frame) = rewind.frames.back() else {
+        return;
+    };
+    match &frame.keyframe {
+        Some(snapshot) => level_state.restore(snapshot),
+        None => level_state.restore_motion(&frame.motion),
+    }
+    *active_checkpoint = frame.checkpoint.clone();
+    player_transform.translation = frame.player_translation;
+    *movement = frame.player_movement.clone();
+    *animation = frame.player_animation;
+    // drop whatever move was queued this tick, it was made from the state being rewound
+    controller.translation = None;
+    // the rewound frame isn't necessarily a keyframe, so start recording with one
+    rewind.since_keyframe = 0;
+}
+
+/// [`System`] that forgets the recorded frames when the player respawns or switches levels, since
+/// the level has been reset under them.
+pub fn clear_rewind_history(mut rewind: ResMut<Rewind>) {
+    rewind.clear();
+}
@@ -8,7 +8,7 @@ use crate::{
     shared::GroupLabel,
 };
 
-use super::{entity::FixedEntityBundle, LevelSystems};
+use super::{entity::FixedEntityBundle, surface::Surface, LevelSystems};
 
 pub struct SemiSolidPlugin;
 impl Plugin for SemiSolidPlugin {
@@ -35,6 +35,8 @@ pub struct SemiSolidPlatformBundle {
     fixed_entity_bundle: FixedEntityBundle,
     semi_solid: SemiSolid,
     dust_surface: DustSurface,
+    #[from_int_grid_cell]
+    surface: Surface,
 }
 
 impl Default for SemiSolidPlatformBundle {
@@ -43,6 +45,7 @@ impl Default for SemiSolidPlatformBundle {
             fixed_entity_bundle: FixedEntityBundle::default(),
             semi_solid: SemiSolid,
             dust_surface: DustSurface::Wood,
+            surface: Surface::default(),
         }
     }
 }
@@ -60,12 +63,7 @@ pub fn update_semisolid_colliders(
     let Ok((movement, player)) = q_player.get_single() else {
         return;
     };
-    const PLAYER_HALF_HEIGHT: f32 = 9.0;
-    let cutoff_height = if movement.crouching {
-        PLAYER_HALF_HEIGHT / 2.0
-    } else {
-        PLAYER_HALF_HEIGHT
-    };
+    let cutoff_height = movement.one_way_cutoff();
 
     for (transform, mut collisions) in q_semisolid.iter_mut() {
         if player.compute_transform().translation.y - transform.compute_transform().translation.y
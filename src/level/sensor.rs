@@ -1,3 +1,5 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
 use bevy::{prelude::*, time::Stopwatch};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -7,6 +9,7 @@ use crate::{
     level::{
         crystal::{CrystalIdent, CrystalToggleEvent},
         platform::ChangePlatformStateEvent,
+        wiring::{EntityIidCache, WireSignalEvent, WireTargets},
     },
     light::segments::simulate_light_sources,
     lighting::LineLight2d,
@@ -26,23 +29,71 @@ impl Plugin for LightSensorPlugin {
                 PreUpdate,
                 add_sensor_sprites.in_set(LevelSystems::Processing),
             )
-            .add_systems(Update, reset_light_sensors.in_set(LevelSystems::Reset))
             .add_systems(
                 FixedUpdate,
-                update_light_sensors
+                (update_light_sensors, update_sensor_mode_sprites)
+                    .chain()
                     .after(simulate_light_sources)
                     .in_set(LevelSystems::Simulation),
             );
     }
 }
 
+/// The default duration for [`SensorMode::Momentary`] pulses and [`SensorMode::TimedHold`] holds,
+/// used when the sensor has no `hold_time` field.
+const DEFAULT_HOLD_MILLIS: i32 = 1000;
+
+/// How a [`LightSensor`] reacts to its meter filling and draining. Read from the optional `mode`
+/// enum field on the Ldtk entity, defaulting to [`SensorMode::Standard`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SensorMode {
+    /// Activates when the meter fills and deactivates when it drains.
+    #[default]
+    Standard,
+    /// Stays active once activated, until the level is reset.
+    Latch,
+    /// Activates for `hold_time` when the meter fills, then turns itself off. The light has to
+    /// leave the sensor before it can pulse again.
+    Momentary,
+    /// Flips between active and inactive each time a new hit fills the meter.
+    Toggle,
+    /// Like [`SensorMode::Standard`], but stays active for `hold_time` after the light leaves.
+    TimedHold,
+}
+
+impl From<&String> for SensorMode {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "Standard" => SensorMode::Standard,
+            "Latch" => SensorMode::Latch,
+            "Momentary" => SensorMode::Momentary,
+            "Toggle" => SensorMode::Toggle,
+            "TimedHold" => SensorMode::TimedHold,
+            _ => panic!("String {} does not represent a SensorMode", value),
+        }
+    }
+}
+
+impl SensorMode {
+    /// Tint applied to the inner ring of the sensor so that each mode can be told apart.
+    fn inner_color(&self) -> Color {
+        match self {
+            SensorMode::Standard => Color::WHITE,
+            SensorMode::Latch => Color::srgb(1.0, 0.8, 0.3),
+            SensorMode::Momentary => Color::srgb(0.5, 1.0, 1.0),
+            SensorMode::Toggle => Color::srgb(1.0, 0.5, 1.0),
+            SensorMode::TimedHold => Color::srgb(0.6, 1.0, 0.5),
+        }
+    }
+}
+
 /// [`Component`] added to entities receptive to light. The
 /// [`activation_timer`](LightSensor::activation_timer) should be initialized in the
 /// `From<&EntityInstance>` implemenation for the [`LightSensorBundle`], if not default.
 ///
 /// The [`Sprite`] on the entity containing a [`LightSensor`] refers to the center part of the
 /// sprite, which will be colored depending on the light that hits it.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct LightSensor {
     /// Stores the cumulative time light has been hitting the sensor
     pub cumulative_exposure: Stopwatch,
@@ -52,18 +103,40 @@ pub struct LightSensor {
     pub hit_by: EnumMap<LightColor, bool>,
     /// Active state of the sensor
     pub is_active: bool,
-    /// The color of the crystals to toggle
-    pub toggle_ident: CrystalIdent,
+    /// The color of the crystals to toggle, if any
+    pub toggle_ident: Option<CrystalIdent>,
     /// Meter's rate of change, per fixed timestep tick.
     rate: f32,
     /// The id of the platform to toggle
     pub platform_id: i32,
+    /// The signal this sensor drives for [`LogicGate`](super::logic::LogicGate) inputs, or -1
+    pub signal_id: i32,
     /// Stored color used to animate the center of the sensor when the light no longer hits it
     stored_color: Color,
+    /// How the sensor reacts to its meter filling and draining
+    pub mode: SensorMode,
+    /// Times [`SensorMode::Momentary`] pulses and [`SensorMode::TimedHold`] holds
+    hold_timer: Timer,
+    /// Set once a [`SensorMode::Momentary`] or [`SensorMode::Toggle`] sensor has fired for the
+    /// current hit, cleared when the light leaves.
+    fired: bool,
+    /// Unit vector the sensor faces, read from the optional `facing` field in degrees
+    /// (counter-clockwise from the right)
+    pub facing: Vec2,
+    /// Cosine of half of the `accept_angle` cone around [`facing`](LightSensor::facing) that beams
+    /// have to come from. -1 accepts beams from every side.
+    accept_cos: f32,
 }
 
 impl LightSensor {
-    fn new(toggle_ident: CrystalIdent, millis: i32, platform_id: i32) -> Self {
+    pub fn new(
+        toggle_ident: Option<CrystalIdent>,
+        millis: i32,
+        platform_id: i32,
+        signal_id: i32,
+        mode: SensorMode,
+        hold_millis: i32,
+    ) -> Self {
         let rate = 1.0 / (millis as f32) * (1000.0 / 64.0);
         LightSensor {
             meter: 0.0,
@@ -73,15 +146,114 @@ impl LightSensor {
             toggle_ident,
             rate,
             platform_id,
+            signal_id,
             stored_color: Color::WHITE,
+            mode,
+            hold_timer: Timer::new(
+                Duration::from_millis(hold_millis.max(0) as u64),
+                TimerMode::Once,
+            ),
+            fired: false,
+            facing: Vec2::Y,
+            accept_cos: -1.0,
         }
     }
 
-    fn reset(&mut self) {
+    /// Whether a beam travelling along `beam_dir` hits the sensor from inside its acceptance cone.
+    pub fn accepts(&self, beam_dir: Vec2) -> bool {
+        self.accept_cos <= -1.0 || (-beam_dir).dot(self.facing) >= self.accept_cos
+    }
+
+    /// How many fixed timesteps a beam would have to hit the sensor for its meter to fill.
+    pub fn ticks_to_fill(&self) -> f32 {
+        ((1.0 - self.meter) / self.rate).ceil()
+    }
+
+    /// Whether the sensor only accepts beams from some directions.
+    pub fn is_directional(&self) -> bool {
+        self.accept_cos > -1.0
+    }
+
+    /// Puts the sensor back in the state it was spawned in. Every [`SensorMode`] should be fully
+    /// reset here.
+    pub fn reset(&mut self) {
         self.meter = 0.0;
         self.hit_by = EnumMap::default();
         self.is_active = false;
         self.cumulative_exposure.reset();
+        self.hold_timer.reset();
+        self.fired = false;
+    }
+
+    /// Advances the sensor by one fixed timestep. Returns `Some(new_state)` if the sensor was
+    /// activated or deactivated during this step.
+    fn step(&mut self, was_hit: bool, delta: Duration) -> Option<bool> {
+        let juice = if was_hit { self.rate } else { -self.rate };
+        let full = self.meter + juice > 1.0;
+        let empty = self.meter + juice < 0.0;
+        self.meter = (self.meter + juice).clamp(0.0, 1.0);
+
+        if !was_hit {
+            self.fired = false;
+        }
+
+        match self.mode {
+            SensorMode::Standard => {
+                if full && !self.is_active {
+                    return self.set_active(true);
+                }
+                if empty && self.is_active {
+                    return self.set_active(false);
+                }
+            }
+            SensorMode::Latch => {
+                if self.is_active {
+                    self.meter = 1.0;
+                } else if full {
+                    return self.set_active(true);
+                }
+            }
+            SensorMode::Momentary => {
+                if self.is_active {
+                    self.hold_timer.tick(delta);
+                    if self.hold_timer.finished() {
+                        return self.set_active(false);
+                    }
+                } else if full && !self.fired {
+                    self.fired = true;
+                    self.hold_timer.reset();
+                    return self.set_active(true);
+                }
+            }
+            SensorMode::Toggle => {
+                if full && !self.fired {
+                    self.fired = true;
+                    return self.set_active(!self.is_active);
+                }
+            }
+            SensorMode::TimedHold => {
+                if was_hit {
+                    self.hold_timer.reset();
+                }
+                if full && !self.is_active {
+                    return self.set_active(true);
+                }
+                if self.is_active && !was_hit {
+                    self.hold_timer.tick(delta);
+                    if self.hold_timer.finished() {
+                        return self.set_active(false);
+                    }
+                    // keep the meter full until the hold runs out
+                    self.meter = 1.0;
+                }
+            }
+        }
+        None
+    }
+
+    fn set_active(&mut self, active: bool) -> Option<bool> {
+        self.is_active = active;
+        Some(active)
     }
 
     fn is_hit(&self) -> bool {
@@ -97,36 +269,73 @@ impl LightSensor {
 
 impl From<&EntityInstance> for LightSensor {
     fn from(entity_instance: &EntityInstance) -> Self {
-        let toggle_color: CrystalColor = entity_instance
-            .get_enum_field("toggle_color")
-            .expect("toggle_color needs to be an enum field on all sensors")
-            .into();
-
-        let id = entity_instance
-            .get_int_field("id")
-            .expect("id needs to be an int field on all sensors");
-
         let millis = *entity_instance
             .get_int_field("activation_time")
             .expect("activation_time needs to be a float field on all sensors");
 
-        let toggle_ident = CrystalIdent {
-            color: toggle_color,
-            id: *id,
-        };
-
         let platform_id = match entity_instance.get_int_field("platform_id") {
             Ok(platform_id) => *platform_id,
             Err(_) => -1,
         };
 
-        LightSensor::new(toggle_ident, millis, platform_id)
+        let signal_id = match entity_instance.get_int_field("signal_id") {
+            Ok(signal_id) => *signal_id,
+            Err(_) => -1,
+        };
+
+        // sensors that only feed a logic gate don't need crystals to toggle
+        let toggle_ident = match (
+            entity_instance.get_enum_field("toggle_color"),
+            entity_instance.get_int_field("id"),
+        ) {
+            (Ok(toggle_color), Ok(id)) => Some(CrystalIdent {
+                color: toggle_color.into(),
+                id: *id,
+            }),
+            _ if signal_id >= 0 => None,
+            _ => panic!("toggle_color and id need to be fields on all sensors without a signal_id"),
+        };
+
+        let mode = match entity_instance.get_enum_field("mode") {
+            Ok(mode) => mode.into(),
+            Err(_) => SensorMode::default(),
+        };
+
+        let hold_millis = match entity_instance.get_int_field("hold_time") {
+            Ok(hold_millis) => *hold_millis,
+            Err(_) => DEFAULT_HOLD_MILLIS,
+        };
+
+        let facing = match entity_instance.get_float_field("facing") {
+            Ok(degrees) => Vec2::from_angle(degrees.to_radians()),
+            Err(_) => Vec2::Y,
+        };
+
+        let accept_cos = match entity_instance.get_float_field("accept_angle") {
+            Ok(degrees) if *degrees < 360.0 => (degrees.max(0.0) / 2.0).to_radians().cos(),
+            _ => -1.0,
+        };
+
+        LightSensor {
+            facing,
+            accept_cos,
+            ..LightSensor::new(
+                toggle_ident,
+                millis,
+                platform_id,
+                signal_id,
+                mode,
+                hold_millis,
+            )
+        }
     }
 }
 
+/// [`System`] that adds the sprites of newly spawned [`LightSensor`]s, rotating directional
+/// sensors so that the top of the sprite points along [`LightSensor::facing`].
 pub fn add_sensor_sprites(
     mut commands: Commands,
-    q_sensors: Query<(Entity, &LightSensor), Added<LightSensor>>,
+    mut q_sensors: Query<(Entity, &LightSensor, &mut Transform), Added<LightSensor>>,
     asset_server: Res<AssetServer>,
 ) {
     if q_sensors.is_empty() {
@@ -137,22 +346,33 @@ pub fn add_sensor_sprites(
     let sensor_outer = asset_server.load("sensor/sensor_outer.png");
     let sensor_center = asset_server.load("sensor/sensor_center.png");
 
-    let inner_sprite = Sprite::from_image(sensor_inner);
+    let mut inner_sprite = Sprite::from_image(sensor_inner);
     let mut outer_sprite = Sprite::from_image(sensor_outer);
     let center_sprite = Sprite::from_image(sensor_center);
 
-    for (entity, sensor) in q_sensors.iter() {
-        outer_sprite.color = sensor.toggle_ident.color.button_color();
+    for (entity, sensor, mut transform) in q_sensors.iter_mut() {
+        if sensor.is_directional() {
+            transform.rotation = Quat::from_rotation_z(sensor.facing.to_angle() - FRAC_PI_2);
+        }
+        inner_sprite.color = sensor.mode.inner_color();
+        outer_sprite.color = sensor
+            .toggle_ident
+            .map_or(Color::WHITE, |ident| ident.color.button_color());
         commands
             .entity(entity)
             .with_children(|sensor| {
-                sensor.spawn(inner_sprite.clone());
+                sensor.spawn((inner_sprite.clone(), SensorInnerMarker));
                 sensor.spawn(outer_sprite.clone());
             })
             .insert(center_sprite.clone());
     }
 }
 
+/// Marker [`Component`] for the inner ring of a [`LightSensor`], which shows the sensor's
+/// [`SensorMode`].
+#[derive(Component)]
+pub struct SensorInnerMarker;
+
 /// [`Bundle`] that includes all the [`Component`]s needed for a [`LightSensor`] to function
 /// properly.
 #[derive(Bundle, LdtkEntity)]
@@ -165,32 +385,19 @@ pub struct LightSensorBundle {
     sensor: Sensor,
     #[from_entity_instance]
     light_sensor: LightSensor,
+    #[from_entity_instance]
+    targets: WireTargets,
     #[with(sensor_point_light)]
     lighting: LineLight2d,
 }
 
 pub fn sensor_point_light(entity_instance: &EntityInstance) -> LineLight2d {
-    let toggle_color: CrystalColor = entity_instance
-        .get_enum_field("toggle_color")
-        .expect("toggle_color needs to be an enum field on all sensors")
-        .into();
-
-    LineLight2d::point(
-        toggle_color
-            .button_color()
-            .to_linear()
-            .to_vec3()
-            .extend(0.5),
-        35.0,
-        0.02,
-    )
-}
+    let color = match entity_instance.get_enum_field("toggle_color") {
+        Ok(toggle_color) => CrystalColor::from(toggle_color).button_color(),
+        Err(_) => Color::WHITE,
+    };
 
-/// [`System`] that resets the [`LightSensor`]s when a [`LevelSwitchEvent`] is received.
-pub fn reset_light_sensors(mut q_sensors: Query<&mut LightSensor>) {
-    for mut sensor in q_sensors.iter_mut() {
-        sensor.reset()
-    }
+    LineLight2d::point(color.to_linear().to_vec3().extend(0.5), 35.0, 0.02)
 }
 
 /// [`System`] that runs on [`Update`], querying each [`LightSensor`] and updating them
@@ -199,15 +406,18 @@ pub fn reset_light_sensors(mut q_sensors: Query<&mut LightSensor>) {
 /// is still imperfect, as while it differs semantically from the previous implementation,
 /// each [`Event`] is generated every frame. Preferably, refactor to include a "yap"-free
 /// implementation across multiple systems to better utilize [`Event`].
+#[allow(clippy::too_many_arguments)]
 pub fn update_light_sensors(
     mut commands: Commands,
-    mut q_sensors: Query<(Entity, &mut LightSensor, &mut Sprite)>,
+    mut q_sensors: Query<(Entity, &mut LightSensor, &mut Sprite, &WireTargets)>,
     mut ev_crystal_toggle: EventWriter<CrystalToggleEvent>,
     mut platform_change: EventWriter<ChangePlatformStateEvent>,
+    mut ev_wire_signal: EventWriter<WireSignalEvent>,
+    entity_iid_cache: Res<EntityIidCache>,
     asset_server: Res<AssetServer>,
     time: Res<Time>,
 ) {
-    for (entity, mut sensor, mut sprite) in q_sensors.iter_mut() {
+    for (entity, mut sensor, mut sprite, targets) in q_sensors.iter_mut() {
         let was_hit = sensor.is_hit();
 
         if was_hit {
@@ -222,44 +432,136 @@ pub fn update_light_sensors(
             sensor.stored_color = Color::srgb(col.x, col.y, col.z);
         }
 
-        let juice = if was_hit { sensor.rate } else { -sensor.rate };
-        sensor.meter += juice;
-
-        let mut send_toggle = || {
-            ev_crystal_toggle.send(CrystalToggleEvent {
-                color: sensor.toggle_ident,
-            });
-            if was_hit {
-                platform_change.send(ChangePlatformStateEvent {
-                    new_state: PlatformState::Play,
-                    id: sensor.platform_id,
-                });
-            } else {
-                platform_change.send(ChangePlatformStateEvent {
-                    new_state: PlatformState::Pause,
-                    id: sensor.platform_id,
+        if let Some(active) = sensor.step(was_hit, time.delta()) {
+            if let Some(toggle_ident) = sensor.toggle_ident {
+                ev_crystal_toggle.send(CrystalToggleEvent {
+                    color: toggle_ident,
                 });
             }
+            platform_change.send(ChangePlatformStateEvent {
+                new_state: if active {
+                    PlatformState::Play
+                } else {
+                    PlatformState::Pause
+                },
+                id: sensor.platform_id,
+            });
+            targets.send(active, &entity_iid_cache, &mut ev_wire_signal);
             commands.entity(entity).with_child((
                 AudioPlayer::new(asset_server.load("sfx/button.wav")),
                 PlaybackSettings::DESPAWN,
             ));
-        };
+        }
 
-        if sensor.meter > 1.0 {
-            if !sensor.is_active {
-                send_toggle();
-                sensor.is_active = true;
+        sprite.color = Color::WHITE.mix(&sensor.stored_color, sensor.meter);
+    }
+}
+
+/// [`System`] that brightens the inner ring of each [`LightSensor`] depending on its
+/// [`SensorMode`], so that latched, pulsing, toggled and held sensors read differently from the
+/// default ones.
+pub fn update_sensor_mode_sprites(
+    q_sensors: Query<(&LightSensor, &Children)>,
+    mut q_inner: Query<&mut Sprite, With<SensorInnerMarker>>,
+) {
+    for (sensor, children) in q_sensors.iter() {
+        let glow = match sensor.mode {
+            SensorMode::Standard => 0.0,
+            SensorMode::Latch | SensorMode::Toggle => {
+                if sensor.is_active {
+                    1.0
+                } else {
+                    0.0
+                }
             }
-            sensor.meter = 1.0;
-        } else if sensor.meter < 0.0 {
-            if sensor.is_active {
-                send_toggle();
-                sensor.is_active = false;
+            SensorMode::Momentary | SensorMode::TimedHold => {
+                if sensor.is_active {
+                    1.0 - sensor.hold_timer.fraction()
+                } else {
+                    0.0
+                }
             }
-            sensor.meter = 0.0;
+        };
+        let base = sensor.mode.inner_color();
+        let lit = (base.to_linear() * 2.0).with_alpha(1.0);
+
+        for child in children.iter() {
+            let Ok(mut inner_sprite) = q_inner.get_mut(*child) else {
+                continue;
+            };
+            inner_sprite.color = base.mix(&Color::from(lit), glow);
         }
+    }
+}
 
-        sprite.color = Color::WHITE.mix(&sensor.stored_color, sensor.meter);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(1000 / 64);
+
+    fn sensor(mode: SensorMode) -> LightSensor {
+        // 64 millis of activation time fills the meter in 4 ticks
+        LightSensor::new(None, 64, -1, -1, mode, 64)
+    }
+
+    fn run(sensor: &mut LightSensor, hit: bool, ticks: usize) -> Vec<bool> {
+        (0..ticks).filter_map(|_| sensor.step(hit, TICK)).collect()
+    }
+
+    #[test]
+    fn standard_follows_light() {
+        let mut s = sensor(SensorMode::Standard);
+        assert_eq!(run(&mut s, true, 10), vec![true]);
+        assert_eq!(run(&mut s, false, 10), vec![false]);
+    }
+
+    #[test]
+    fn latch_never_deactivates() {
+        let mut s = sensor(SensorMode::Latch);
+        assert_eq!(run(&mut s, true, 10), vec![true]);
+        assert!(run(&mut s, false, 100).is_empty());
+        assert!(s.is_active);
+        s.reset();
+        assert!(!s.is_active);
+    }
+
+    #[test]
+    fn momentary_pulses_once_per_hit() {
+        let mut s = sensor(SensorMode::Momentary);
+        assert_eq!(run(&mut s, true, 20), vec![true, false]);
+        assert!(run(&mut s, false, 10).is_empty());
+        assert_eq!(run(&mut s, true, 20), vec![true, false]);
+    }
+
+    #[test]
+    fn toggle_flips_on_new_hits() {
+        let mut s = sensor(SensorMode::Toggle);
+        assert_eq!(run(&mut s, true, 10), vec![true]);
+        assert!(run(&mut s, false, 10).is_empty());
+        assert_eq!(run(&mut s, true, 10), vec![false]);
+    }
+
+    #[test]
+    fn directional_sensor_only_accepts_cone() {
+        let mut s = sensor(SensorMode::Standard);
+        assert!(s.accepts(Vec2::X) && s.accepts(Vec2::NEG_Y));
+
+        // faces down and accepts beams within 45 degrees of straight up
+        s.facing = Vec2::NEG_Y;
+        s.accept_cos = (45.0_f32).to_radians().cos();
+        assert!(s.accepts(Vec2::Y));
+        assert!(s.accepts(Vec2::new(0.5, 1.0).normalize()));
+        assert!(!s.accepts(Vec2::X));
+        assert!(!s.accepts(Vec2::NEG_Y));
+    }
+
+    #[test]
+    fn timed_hold_outlasts_light() {
+        let mut s = sensor(SensorMode::TimedHold);
+        assert_eq!(run(&mut s, true, 10), vec![true]);
+        // hold of 64 millis lasts 4 ticks
+        assert!(run(&mut s, false, 3).is_empty());
+        assert_eq!(run(&mut s, false, 10), vec![false]);
     }
 }
@@ -1,27 +1,22 @@
-use std::{cmp::Ordering, time::Duration};
-
-use bevy::{ecs::system::SystemId, prelude::*};
+use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use enum_map::EnumMap;
 
 use crate::{
     animation::AnimationConfig,
-    camera::{
-        camera_position_from_level, camera_position_from_level_with_scale, CameraControlType,
-        CameraMoveEvent, CameraZoomEvent,
-    },
+    cutscene::{CutsceneSignal, StartCutsceneEvent},
     light::LightColor,
     lighting::LineLight2d,
     player::{
+        grapple::LightGrapple,
         light::{
             despawn_angle_increments_indicators, despawn_angle_indicator, should_shoot_light,
             PlayerLightInventory,
         },
-        InputLocked, PlayerHurtMarker, PlayerMarker,
+        PlayerHurtMarker, PlayerMarker,
     },
-    shared::{AnimationState, GameState, ResetLevel},
-    sound::{BgmMarker, Fade, FadeSettings, BGM_VOLUME},
+    shared::GameState,
 };
 
 use super::{entity::FixedEntityBundle, CurrentLevel, LevelSystems};
@@ -32,7 +27,7 @@ impl Plugin for CrystalShardPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ShardAnimationEvent>()
             .init_resource::<CrystalShardMods>()
-            .init_resource::<ShardAnimationCallbacks>()
+            .init_resource::<CollectingShard>()
             .register_ldtk_entity::<CrystalShardBundle>("CrystalShard")
             .add_systems(
                 PreUpdate,
@@ -50,40 +45,56 @@ impl Plugin for CrystalShardPlugin {
                 )
                     .run_if(on_event::<ShardAnimationEvent>),
             )
-            .add_systems(
-                Update,
-                shard_dialogue.run_if(in_state(AnimationState::ShardDialogue)),
-            )
-            .add_systems(
-                Update,
-                (
-                    reset_shard_visibility,
-                    (reset_shard_effects_on_kill, reset_shard_effects_cache).chain(),
-                )
-                    .in_set(LevelSystems::Reset),
-            )
             .add_systems(
                 FixedUpdate,
-                (on_player_intersect_shard, start_shard_animation)
+                (on_player_intersect_shard, start_shard_cutscene)
                     .chain()
                     .in_set(LevelSystems::Simulation),
-            );
+            )
+            .add_systems(Update, collect_shard.run_if(on_event::<CutsceneSignal>));
     }
 }
 
 #[derive(Component, Debug)]
 pub struct CrystalShard {
     light_color: LightColor,
+    /// Number of extra beams the shard grants, from the optional `charges` field
+    charges: u32,
+    /// Whether the shard unlocks the [`LightGrapple`], from the optional `unlocks_grapple` field
+    unlocks_grapple: bool,
+    /// Id of the dialogue played when the shard is collected, from the optional `dialogue` field.
+    /// Defaults to `shard_<color>`.
+    dialogue: String,
 }
 
 impl From<&EntityInstance> for CrystalShard {
     fn from(value: &EntityInstance) -> Self {
-        let light_color = value
+        let color_name = value
             .get_enum_field("light_color")
-            .expect("All crystal shards should have a light_color enum field")
-            .into();
+            .expect("All crystal shards should have a light_color enum field");
+        let light_color = color_name.into();
+
+        let charges = match value.get_int_field("charges") {
+            Ok(charges) => (*charges).max(0) as u32,
+            Err(_) => 1,
+        };
+
+        let unlocks_grapple = value
+            .get_bool_field("unlocks_grapple")
+            .copied()
+            .unwrap_or(false);
 
-        Self { light_color }
+        let dialogue = match value.get_maybe_string_field("dialogue") {
+            Ok(Some(dialogue)) => dialogue.clone(),
+            _ => format!("shard_{}", color_name.to_lowercase()),
+        };
+
+        Self {
+            light_color,
+            charges,
+            unlocks_grapple,
+            dialogue,
+        }
     }
 }
 
@@ -108,9 +119,10 @@ pub fn crystal_shard_light(entity_instance: &EntityInstance) -> LineLight2d {
     LineLight2d::point(light_color.lighting_color().extend(1.0), 40.0, 0.015)
 }
 
-#[derive(Resource, Default)]
-/// Sets a value to true if the light color was obtained from a crystal in the current level
-pub struct CrystalShardMods(EnumMap<LightColor, bool>);
+#[derive(Resource, Default, Clone, Debug)]
+/// Number of beams of each color granted by crystal shards since the player last respawned, which
+/// are taken away again if they die
+pub struct CrystalShardMods(EnumMap<LightColor, u32>);
 
 pub fn add_crystal_shard_sprites(
     mut commands: Commands,
@@ -153,39 +165,10 @@ pub fn add_crystal_shard_sprites(
     }
 }
 
-pub fn reset_shard_visibility(mut q_shards: Query<&mut Visibility, With<CrystalShard>>) {
-    for mut visibility in q_shards.iter_mut() {
-        *visibility = Visibility::Visible;
-    }
-}
-pub fn reset_shard_effects_cache(mut shard_mods: ResMut<CrystalShardMods>) {
-    for (_, is_temporary) in shard_mods.0.iter_mut() {
-        *is_temporary = false;
-    }
-}
-
-pub fn reset_shard_effects_on_kill(
-    mut current_level: ResMut<CurrentLevel>,
-    mut shard_mods: ResMut<CrystalShardMods>,
-    mut ev_reset_level: EventReader<ResetLevel>,
-) {
-    if !ev_reset_level.read().any(|ev| *ev == ResetLevel::Respawn) {
-        return;
-    }
-
-    for (color, is_temporary) in shard_mods.0.iter_mut() {
-        if *is_temporary {
-            current_level.allowed_colors[color] = false;
-        }
-    }
-}
-
 pub fn on_player_intersect_shard(
     q_shards: Query<(Entity, &CrystalShard, &Visibility)>,
     mut q_player: Query<Entity, With<PlayerHurtMarker>>,
     rapier_context: Query<&RapierContext>,
-    current_level: Res<CurrentLevel>,
-    mut shard_mods: ResMut<CrystalShardMods>,
     mut ev_shard_animation: EventWriter<ShardAnimationEvent>,
 ) {
     let Ok(rapier_context) = rapier_context.get_single() else {
@@ -202,10 +185,6 @@ pub fn on_player_intersect_shard(
         }
         if let Some(true) = rapier_context.intersection_pair(player_entity, shard_entity) {
             ev_shard_animation.send(ShardAnimationEvent((shard_entity, shard.light_color)));
-            if !current_level.allowed_colors[shard.light_color] {
-                // only mark as temporary modification if not actually allowed
-                shard_mods.0[shard.light_color] = true;
-            }
         }
     }
 }
@@ -213,291 +192,76 @@ pub fn on_player_intersect_shard(
 #[derive(Event)]
 pub struct ShardAnimationEvent((Entity, LightColor));
 
-#[derive(Resource)]
-pub struct ShardAnimationCallbacks {
-    for_shard: Option<(Entity, LightColor)>,
-    cb: [SystemId; 3],
-}
-
-impl FromWorld for ShardAnimationCallbacks {
-    fn from_world(world: &mut World) -> Self {
-        Self {
-            for_shard: None,
-            cb: [
-                world.register_system(on_shard_zoom_in_finished),
-                world.register_system(on_shard_text_read_finish),
-                world.register_system(on_shard_zoom_back_finish),
-            ],
-        }
-    }
-}
+/// Name of the [`CutsceneSignal`] that gives the player the shard they are collecting.
+const COLLECT_SHARD_SIGNAL: &str = "collect_shard";
 
-const SHARD_FADE_DURATION: Duration = Duration::from_millis(500);
-const SHARD_FADE_VOLUME: f32 = 0.1;
+/// [`Resource`] holding the shard whose cutscene is playing.
+#[derive(Resource, Default)]
+pub struct CollectingShard(Option<(Entity, LightColor)>);
 
-#[allow(clippy::too_many_arguments, clippy::type_complexity)]
-pub fn start_shard_animation(
-    mut commands: Commands,
+/// [`System`] that plays the `shard` cutscene when the player touches a crystal shard.
+pub fn start_shard_cutscene(
     cur_game_state: Res<State<GameState>>,
-    mut next_game_state: ResMut<NextState<GameState>>,
-    mut next_anim_state: ResMut<NextState<AnimationState>>,
-    mut ev_move_camera: EventWriter<CameraMoveEvent>,
-    mut ev_zoom_camera: EventWriter<CameraZoomEvent>,
     mut ev_shard_animation: EventReader<ShardAnimationEvent>,
-    q_player: Query<(Entity, &GlobalTransform), With<PlayerMarker>>,
-    mut shard_anim_cbs: ResMut<ShardAnimationCallbacks>,
-    current_level: Res<CurrentLevel>,
-    asset_server: Res<AssetServer>,
-    q_bgm: Query<
-        (&AudioSink, Entity, Option<&FadeSettings>),
-        (With<BgmMarker>, Without<PlayerMarker>),
-    >,
+    mut ev_start_cutscene: EventWriter<StartCutsceneEvent>,
+    mut collecting_shard: ResMut<CollectingShard>,
+    q_shards: Query<&CrystalShard>,
 ) {
-    if ev_shard_animation.is_empty() {
+    let Some(ShardAnimationEvent(shard_info)) = ev_shard_animation.read().next() else {
         return;
-    }
-    let shard_info = ev_shard_animation.read().next().unwrap().0;
-
-    shard_anim_cbs.for_shard = Some(shard_info);
-    if *cur_game_state.get() == GameState::Animating {
+    };
+    if *cur_game_state.get() != GameState::Playing {
         return;
     }
-    let Ok((player_entity, player_transform)) = q_player.get_single() else {
+    let Ok(shard) = q_shards.get(shard_info.0) else {
         return;
     };
-    commands.entity(player_entity).insert(InputLocked);
-
-    commands.entity(player_entity).with_child((
-        AudioPlayer::new(asset_server.load("sfx/shard_acquire.wav")),
-        PlaybackSettings::DESPAWN,
-    ));
-
-    for (sink, bgm, fade_settings) in q_bgm.iter() {
-        // FIXME: If the entity has FadeSettings::Despawn fade just let it despawn
-        if fade_settings.is_some_and(|settings| *settings == FadeSettings::Despawn) {
-            continue;
-        }
-        commands.entity(bgm).insert(Fade::new(
-            SHARD_FADE_DURATION,
-            sink.volume(),
-            SHARD_FADE_VOLUME,
-        ));
-    }
-
-    const SHARD_ANIMATION_CAMERA_SCALE: f32 = 0.75;
 
-    ev_zoom_camera.send(CameraZoomEvent {
-        scale: SHARD_ANIMATION_CAMERA_SCALE,
-        variant: CameraControlType::Animated {
-            duration: Duration::from_millis(500),
-            ease_fn: EaseFunction::SineInOut,
-            callback: None,
-        },
-    });
-
-    let camera_pos = camera_position_from_level_with_scale(
-        current_level.level_box,
-        player_transform.translation().xy(),
-        SHARD_ANIMATION_CAMERA_SCALE,
-    );
-
-    ev_move_camera.send(CameraMoveEvent {
-        to: camera_pos,
-        variant: CameraControlType::Animated {
-            duration: Duration::from_millis(500),
-            ease_fn: EaseFunction::SineInOut,
-            callback: Some(shard_anim_cbs.cb[0]),
-        },
+    collecting_shard.0 = Some(*shard_info);
+    ev_start_cutscene.send(StartCutsceneEvent {
+        id: "shard".to_string(),
+        dialogue: Some(shard.dialogue.clone()),
     });
-    next_game_state.set(GameState::Animating);
-    next_anim_state.set(AnimationState::Shard);
-}
-
-#[derive(Component)]
-pub struct ShardBoxMarker;
-
-#[derive(Component)]
-pub struct ShardTextMarker;
-
-#[derive(Component)]
-pub struct ShardImageMarker;
-
-pub fn on_shard_zoom_in_finished(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut next_anim_state: ResMut<NextState<AnimationState>>,
-) {
-    let font = TextFont {
-        font: asset_server.load("fonts/Outfit-Medium.ttf"),
-        ..default()
-    };
-
-    commands
-        .spawn((
-            Node {
-                width: Val::Percent(100.),
-                height: Val::Percent(100.),
-                padding: UiRect::all(Val::Px(32.)),
-                display: Display::Flex,
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            ShardBoxMarker,
-        ))
-        .with_children(|container| {
-            container
-                .spawn((
-                    Node {
-                        width: Val::Percent(100.),
-                        max_width: Val::Px(1280.),
-                        height: Val::Auto,
-                        aspect_ratio: Some(2775. / 630.), // FIXME: magic values!
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        padding: UiRect::new(
-                            Val::Px(200.),
-                            Val::Px(200.),
-                            Val::Px(32.),
-                            Val::Px(32.),
-                        ),
-                        ..default()
-                    },
-                    ShardImageMarker,
-                    ImageNode::new(asset_server.load("dialogue-box-lyra-happy.png")),
-                ))
-                .with_children(|text_box| {
-                    text_box.spawn((
-                        Node::default(),
-                        font.clone().with_font_size(24.),
-                        TextLayout::new_with_justify(JustifyText::Center),
-                        Text::new(""),
-                        ShardTextMarker,
-                    ));
-                });
-        });
-    next_anim_state.set(AnimationState::ShardDialogue);
 }
 
-#[allow(clippy::too_many_arguments)]
-// FIXME: copied LOL
-pub fn shard_dialogue(
+/// [`System`] that gives the player the shard they are collecting when its cutscene sends the
+/// [`COLLECT_SHARD_SIGNAL`].
+#[allow(clippy::type_complexity)]
+pub fn collect_shard(
     mut commands: Commands,
-    mut q_shard_text: Query<&mut Text, With<ShardTextMarker>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mouse: Res<ButtonInput<MouseButton>>,
-    callbacks: ResMut<ShardAnimationCallbacks>,
-    mut timer: Local<Option<Timer>>,
-    time: Res<Time>,
-    mut next_anim_state: ResMut<NextState<AnimationState>>,
+    mut ev_cutscene_signal: EventReader<CutsceneSignal>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut shard_mods: ResMut<CrystalShardMods>,
+    q_shards: Query<&CrystalShard>,
+    mut q_player: Query<(&mut PlayerLightInventory, &mut LightGrapple), With<PlayerMarker>>,
+    mut collecting_shard: ResMut<CollectingShard>,
 ) {
-    let mut text = q_shard_text
-        .get_single_mut()
-        .expect("Shard dialogue text should exist!");
-
-    let Some((_, shard_color)) = callbacks.for_shard else {
+    if !ev_cutscene_signal
+        .read()
+        .any(|signal| signal.0 == COLLECT_SHARD_SIGNAL)
+    {
         return;
-    };
-
-    if (*timer).is_none() {
-        *timer = Some(Timer::new(Duration::from_millis(20), TimerMode::Repeating));
     }
-
-    let shard_text = match shard_color {
-        LightColor::Green => "Oh good, the first piece of the Divine Prism. This should let me shoot a bouncing light beam.",
-        LightColor::Blue => "Blue light, formerly known as the light of harmony. Could this one shoot through the active blue crystals above me?",
-        LightColor::White => "A different feeling than before... could this color have a special reflective properties?",
-        LightColor::Purple => "This one's even more powerful... I should be able to bounce this one more than once.",
-        LightColor::Black => "Devs Only!!!",
+    let Some((shard_entity, shard_color)) = collecting_shard.0.take() else {
+        return;
     };
-
-    if keys.any_just_pressed([KeyCode::Space, KeyCode::Enter])
-        || mouse.just_pressed(MouseButton::Left)
-    {
-        match text.len().cmp(&shard_text.len()) {
-            Ordering::Less => {
-                //if animating the text rn, display it fully
-                *text = shard_text.into();
-            }
-            Ordering::Equal => {
-                next_anim_state.set(AnimationState::Shard);
-                commands.run_system(callbacks.cb[1]);
-            }
-            _ => {}
-        }
+    let Ok((mut player_light_inventory, mut grapple)) = q_player.get_single_mut() else {
         return;
-    }
-
-    timer.as_mut().unwrap().tick(time.delta());
-    // normal function call, animate text and then  update it
-    if text.len() < shard_text.len() && timer.as_ref().unwrap().just_finished() {
-        *text = shard_text[0..text.len() + 1].into();
-    }
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn on_shard_text_read_finish(
-    mut commands: Commands,
-    mut ev_move_camera: EventWriter<CameraMoveEvent>,
-    mut ev_zoom_camera: EventWriter<CameraZoomEvent>,
-    mut current_level: ResMut<CurrentLevel>,
-    mut q_player: Query<(&GlobalTransform, &mut PlayerLightInventory), With<PlayerMarker>>,
-    q_shard_text: Query<Entity, With<ShardBoxMarker>>,
-    shard_anim_cbs: Res<ShardAnimationCallbacks>,
-    q_bgm: Query<Entity, (With<BgmMarker>, Without<ShardBoxMarker>)>,
-) {
-    let (player_transform, mut player_light_inventory) = q_player
-        .get_single_mut()
-        .expect("Player should not die during shard transition");
-    let shard_text = q_shard_text
-        .get_single()
-        .expect("Shard text should not die during shard transition");
-    let (shard_entity, shard_color) = shard_anim_cbs
-        .for_shard
-        .expect("Shard animation should be for a shard");
+    };
 
-    commands.entity(shard_text).despawn_recursive();
     commands.entity(shard_entity).insert(Visibility::Hidden);
     player_light_inventory.current_color = Some(shard_color);
     current_level.allowed_colors[shard_color] = true;
 
-    let camera_pos =
-        camera_position_from_level(current_level.level_box, player_transform.translation().xy());
-
-    ev_move_camera.send(CameraMoveEvent {
-        to: camera_pos,
-        variant: CameraControlType::Animated {
-            duration: Duration::from_millis(500),
-            ease_fn: EaseFunction::SineInOut,
-            callback: Some(shard_anim_cbs.cb[2]),
-        },
-    });
-    ev_zoom_camera.send(CameraZoomEvent {
-        scale: 1.,
-        variant: CameraControlType::Animated {
-            duration: Duration::from_millis(500),
-            ease_fn: EaseFunction::SineInOut,
-            callback: None,
-        },
-    });
-
-    for bgm in q_bgm.iter() {
-        commands.entity(bgm).insert(Fade::new(
-            SHARD_FADE_DURATION,
-            SHARD_FADE_VOLUME,
-            BGM_VOLUME,
-        ));
-    }
-}
-
-pub fn on_shard_zoom_back_finish(
-    mut commands: Commands,
-    mut next_game_state: ResMut<NextState<GameState>>,
-    q_player: Query<Entity, With<PlayerMarker>>,
-) {
-    let player_entity = q_player
-        .get_single()
-        .expect("Player should not die during shard transition");
-    next_game_state.set(GameState::Playing);
-    commands.entity(player_entity).remove::<InputLocked>();
+    // the charges are taken away again if the player dies, when the level is restored to its
+    // LevelStartSnapshot
+    let (charges, unlocks_grapple) = q_shards
+        .get(shard_entity)
+        .map(|shard| (shard.charges, shard.unlocks_grapple))
+        .unwrap_or((1, false));
+    // unlocked abilities are kept when the player dies
+    grapple.unlocked |= unlocks_grapple;
+    current_level.beam_counts[shard_color] += charges;
+    player_light_inventory.sources[shard_color] += charges;
+    shard_mods.0[shard_color] += charges;
 }
@@ -0,0 +1,533 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_ldtk::LevelIid;
+use bevy_ecs_tilemap::tiles::TileTextureIndex;
+use enum_map::EnumMap;
+
+use crate::{
+    light::{
+        segments::{
+            cleanup_light_sources, despawn_light_beam, LightSegmentCache, PrevLightBeamPlayback,
+        },
+        set_emitter_enabled, EmitterBeam, LightBeamLDTKSource, LightBeamSource, LightColor,
+        LightSourceZMarker,
+    },
+    player::{
+        light::{spawn_player_beam, PlayerBeam, PlayerLightInventory},
+        PlayerMarker,
+    },
+    shared::ResetLevel,
+};
+
+use super::{
+    crystal::{toggle_crystal, toggle_crystal_group, Crystal, CrystalGroup},
+    logic::LogicGate,
+    platform::{MovingPlatform, PlatformState},
+    sensor::LightSensor,
+    shard::{CrystalShard, CrystalShardMods},
+    CurrentLevel, LevelSystems,
+};
+
+/// [`Plugin`] that puts the level back in the state it was loaded in whenever it is reset.
+pub struct LevelSnapshotPlugin;
+
+impl Plugin for LevelSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelStartSnapshot>().add_systems(
+            Update,
+            reset_level_state
+                .after(cleanup_light_sources)
+                .in_set(LevelSystems::Reset),
+        );
+    }
+}
+
+/// A beam shot by the player, as recorded in a [`LevelSnapshot`]. Beams are told apart by when
+/// they were shot, so that a beam that still exists is rewound instead of being shot again.
+#[derive(Debug, Clone)]
+struct BeamSnapshot {
+    source: LightBeamSource,
+    shot_at: Duration,
+}
+
+/// Where a [`MovingPlatform`] is along its path, without the path itself which never changes.
+#[derive(Debug, Clone, Copy)]
+struct PlatformSnapshot {
+    translation: Vec3,
+    curr_segment: IVec2,
+    previous_segment: IVec2,
+    curr_segment_index: i32,
+    curr_state: PlatformState,
+    has_activated: bool,
+    arc_time: f32,
+    current_position: Vec2,
+}
+
+impl PlatformSnapshot {
+    fn new(platform: &MovingPlatform, transform: &Transform) -> Self {
+        PlatformSnapshot {
+            translation: transform.translation,
+            curr_segment: platform.curr_segment,
+            previous_segment: platform.previous_segment,
+            curr_segment_index: platform.curr_segment_index,
+            curr_state: platform.curr_state,
+            has_activated: platform.has_activated,
+            arc_time: platform.arc_time,
+            current_position: platform.current_position,
+        }
+    }
+
+    fn restore(&self, platform: &mut MovingPlatform, transform: &mut Transform) {
+        transform.translation = self.translation;
+        platform.curr_segment = self.curr_segment;
+        platform.previous_segment = self.previous_segment;
+        platform.curr_segment_index = self.curr_segment_index;
+        platform.curr_state = self.curr_state;
+        platform.has_activated = self.has_activated;
+        platform.arc_time = self.arc_time;
+        platform.current_position = self.current_position;
+    }
+}
+
+/// The parts of a [`LevelSnapshot`] that change every tick: moving platforms and the player's
+/// beams. Small enough to be taken every tick, see [`LevelState::motion`].
+#[derive(Debug, Clone)]
+pub struct LevelMotion {
+    platforms: Vec<(Entity, PlatformSnapshot)>,
+    beams: Vec<BeamSnapshot>,
+}
+
+/// The puzzle state of the loaded levels at one point in time, taken and restored with
+/// [`LevelState`].
+#[derive(Debug, Clone)]
+pub struct LevelSnapshot {
+    sensors: Vec<(Entity, LightSensor)>,
+    gates: Vec<(Entity, LogicGate)>,
+    crystals: Vec<(Entity, bool)>,
+    crystal_groups: Vec<(Entity, bool)>,
+    motion: LevelMotion,
+    emitters: Vec<(Entity, bool)>,
+    collected_shards: Vec<Entity>,
+    shard_mods: CrystalShardMods,
+    allowed_colors: EnumMap<LightColor, bool>,
+    beam_counts: EnumMap<LightColor, u32>,
+    current_color: Option<LightColor>,
+    sources: EnumMap<LightColor, u32>,
+}
+
+/// [`Resource`] holding the [`LevelSnapshot`] taken when the current level was loaded.
+#[derive(Resource, Default, Debug)]
+pub struct LevelStartSnapshot(Option<LevelSnapshot>);
+
+/// [`SystemParam`] with access to every puzzle [`Component`] of the level, used to take a
+/// [`LevelSnapshot`] and to restore one all at once. Restoring doesn't send any
+/// [`CrystalToggleEvent`](super::crystal::CrystalToggleEvent) or wire signal, since the sensors,
+/// gates and everything they drive are restored together.
+#[derive(SystemParam)]
+pub struct LevelState<'w, 's> {
+    commands: Commands<'w, 's>,
+    asset_server: Res<'w, AssetServer>,
+    current_level: ResMut<'w, CurrentLevel>,
+    shard_mods: ResMut<'w, CrystalShardMods>,
+    segment_cache: ResMut<'w, LightSegmentCache>,
+    q_sensor_entities: Query<'w, 's, Entity, With<LightSensor>>,
+    q_sensors: Query<'w, 's, &'static mut LightSensor>,
+    q_gates: Query<'w, 's, (Entity, &'static mut LogicGate)>,
+    q_crystals: Query<'w, 's, (Entity, &'static mut Crystal, &'static mut TileTextureIndex)>,
+    q_crystal_groups: Query<'w, 's, (Entity, &'static mut CrystalGroup)>,
+    q_platforms: Query<
+        'w,
+        's,
+        (Entity, &'static mut MovingPlatform, &'static mut Transform),
+        Without<LightSourceZMarker>,
+    >,
+    q_shards: Query<'w, 's, (Entity, &'static mut Visibility), With<CrystalShard>>,
+    q_emitters: Query<'w, 's, (Entity, &'static mut LightBeamLDTKSource)>,
+    q_emitter_beams: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static LightBeamSource,
+            &'static PrevLightBeamPlayback,
+            &'static EmitterBeam,
+        ),
+        Without<PlayerBeam>,
+    >,
+    q_player_beams: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut LightBeamSource,
+            &'static PrevLightBeamPlayback,
+            &'static PlayerBeam,
+        ),
+    >,
+    q_inventory: Query<'w, 's, &'static mut PlayerLightInventory, With<PlayerMarker>>,
+    q_light_source_z: Query<'w, 's, &'static Transform, With<LightSourceZMarker>>,
+}
+
+impl LevelState<'_, '_> {
+    /// The [`LevelIid`] of the [`CurrentLevel`], since systems using [`LevelState`] can't also
+    /// access it directly.
+    pub fn current_level_iid(&self) -> &LevelIid {
+        &self.current_level.level_iid
+    }
+
+    /// Records the current state of every puzzle [`Component`].
+    pub fn snapshot(&self) -> LevelSnapshot {
+        let inventory = self.q_inventory.get_single().ok();
+        LevelSnapshot {
+            sensors: self
+                .q_sensor_entities
+                .iter()
+                .filter_map(|entity| Some((entity, self.q_sensors.get(entity).ok()?.clone())))
+                .collect(),
+            gates: self
+                .q_gates
+                .iter()
+                .map(|(entity, gate)| (entity, gate.clone()))
+                .collect(),
+            crystals: self
+                .q_crystals
+                .iter()
+                .map(|(entity, crystal, _)| (entity, crystal.active))
+                .collect(),
+            crystal_groups: self
+                .q_crystal_groups
+                .iter()
+                .map(|(entity, group)| (entity, group.representative.active))
+                .collect(),
+            motion: self.motion(),
+            emitters: self
+                .q_emitters
+                .iter()
+                .map(|(entity, emitter)| (entity, emitter.enabled))
+                .collect(),
+            collected_shards: self
+                .q_shards
+                .iter()
+                .filter(|(_, visibility)| **visibility == Visibility::Hidden)
+                .map(|(entity, _)| entity)
+                .collect(),
+            shard_mods: self.shard_mods.clone(),
+            allowed_colors: self.current_level.allowed_colors,
+            beam_counts: self.current_level.beam_counts,
+            current_color: inventory.and_then(|inventory| inventory.current_color),
+            sources: inventory
+                .map(|inventory| inventory.sources)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Records the state every puzzle [`Component`] was spawned in, along with the colors and
+    /// beams the [`CurrentLevel`] starts with. Unlike [`LevelState::snapshot`], this doesn't depend
+    /// on what happened to the loaded levels before, like platforms moving in a neighboring level.
+    pub fn initial_snapshot(&self) -> LevelSnapshot {
+        LevelSnapshot {
+            sensors: self
+                .q_sensor_entities
+                .iter()
+                .filter_map(|entity| {
+                    let mut sensor = self.q_sensors.get(entity).ok()?.clone();
+                    sensor.reset();
+                    Some((entity, sensor))
+                })
+                .collect(),
+            gates: self
+                .q_gates
+                .iter()
+                .map(|(entity, gate)| {
+                    let mut gate = gate.clone();
+                    gate.reset();
+                    (entity, gate)
+                })
+                .collect(),
+            crystals: self
+                .q_crystals
+                .iter()
+                .map(|(entity, crystal, _)| (entity, crystal.init_active))
+                .collect(),
+            crystal_groups: self
+                .q_crystal_groups
+                .iter()
+                .map(|(entity, group)| (entity, group.representative.init_active))
+                .collect(),
+            motion: LevelMotion {
+                platforms: self
+                    .q_platforms
+                    .iter()
+                    .map(|(entity, platform, _)| {
+                        let mut platform = platform.clone();
+                        let transform = Transform::from_translation(platform.reset());
+                        (entity, PlatformSnapshot::new(&platform, &transform))
+                    })
+                    .collect(),
+                beams: vec![],
+            },
+            emitters: self
+                .q_emitters
+                .iter()
+                .map(|(entity, emitter)| (entity, emitter.init_enabled))
+                .collect(),
+            collected_shards: vec![],
+            shard_mods: CrystalShardMods::default(),
+            allowed_colors: self.current_level.allowed_colors,
+            beam_counts: self.current_level.beam_counts,
+            current_color: self
+                .q_inventory
+                .get_single()
+                .ok()
+                .and_then(|inventory| inventory.current_color),
+            sources: self.current_level.beam_counts,
+        }
+    }
+
+    /// Records where the moving platforms and the player's beams are.
+    pub fn motion(&self) -> LevelMotion {
+        LevelMotion {
+            platforms: self
+                .q_platforms
+                .iter()
+                .map(|(entity, platform, transform)| {
+                    (entity, PlatformSnapshot::new(platform, transform))
+                })
+                .collect(),
+            beams: self
+                .q_player_beams
+                .iter()
+                .map(|(_, source, _, beam)| BeamSnapshot {
+                    source: source.clone(),
+                    shot_at: beam.shot_at,
+                })
+                .collect(),
+        }
+    }
+
+    /// Moves the platforms and the player's beams back to where `motion` recorded them. Beams
+    /// that were despawned since are shot again, and beams shot since are despawned, taking or
+    /// giving back their color from the [`PlayerLightInventory`].
+    pub fn restore_motion(&mut self, motion: &LevelMotion) {
+        // beams that still exist are rewound, the others are despawned or shot again
+        let mut inventory = self.q_inventory.get_single_mut().ok();
+        let mut remaining_beams = motion.beams.clone();
+        for (entity, mut source, prev_playback, beam) in self.q_player_beams.iter_mut() {
+            match remaining_beams
+                .iter()
+                .position(|recorded| recorded.shot_at == beam.shot_at)
+            {
+                Some(i) => *source = remaining_beams.swap_remove(i).source,
+                None => {
+                    despawn_light_beam(
+                        &mut self.commands,
+                        entity,
+                        &source,
+                        prev_playback,
+                        &mut self.q_sensors,
+                        &mut self.segment_cache,
+                    );
+                    if let Some(inventory) = inventory.as_mut() {
+                        inventory.sources[source.color] += 1;
+                    }
+                }
+            }
+        }
+        let z = self
+            .q_light_source_z
+            .get_single()
+            .map(|transform| transform.translation.z)
+            .unwrap_or_default();
+        for recorded in remaining_beams {
+            if let Some(inventory) = inventory.as_mut() {
+                inventory.sources[recorded.source.color] =
+                    inventory.sources[recorded.source.color].saturating_sub(1);
+            }
+            spawn_player_beam(
+                &mut self.commands,
+                &self.asset_server,
+                recorded.source,
+                z,
+                recorded.shot_at,
+            );
+        }
+
+        for (entity, recorded) in motion.platforms.iter() {
+            if let Ok((_, mut platform, mut transform)) = self.q_platforms.get_mut(*entity) {
+                recorded.restore(&mut platform, &mut transform);
+            }
+        }
+    }
+
+    /// Puts every puzzle [`Component`] back in the state recorded by `snapshot`. Entities that
+    /// were despawned since are skipped, except for the player's beams which are shot again.
+    pub fn restore(&mut self, snapshot: &LevelSnapshot) {
+        for (entity, enabled) in snapshot.emitters.iter() {
+            let Ok((_, mut emitter)) = self.q_emitters.get_mut(*entity) else {
+                continue;
+            };
+            set_emitter_enabled(
+                &mut self.commands,
+                *entity,
+                &mut emitter,
+                *enabled,
+                &self.q_emitter_beams,
+                &mut self.q_sensors,
+                &mut self.segment_cache,
+            );
+        }
+
+        self.restore_motion(&snapshot.motion);
+
+        for (entity, recorded) in snapshot.sensors.iter() {
+            if let Ok(mut sensor) = self.q_sensors.get_mut(*entity) {
+                *sensor = recorded.clone();
+            }
+        }
+        for (entity, recorded) in snapshot.gates.iter() {
+            if let Ok((_, mut gate)) = self.q_gates.get_mut(*entity) {
+                *gate = recorded.clone();
+            }
+        }
+        for (entity, active) in snapshot.crystals.iter() {
+            let Ok((_, mut crystal, mut index)) = self.q_crystals.get_mut(*entity) else {
+                continue;
+            };
+            if crystal.active != *active {
+                toggle_crystal(&mut crystal, &mut index);
+            }
+        }
+        for (entity, active) in snapshot.crystal_groups.iter() {
+            let Ok((_, mut crystal_group)) = self.q_crystal_groups.get_mut(*entity) else {
+                continue;
+            };
+            if crystal_group.representative.active != *active {
+                toggle_crystal_group(&mut self.commands, *entity, &mut crystal_group);
+            }
+        }
+        for (entity, mut visibility) in self.q_shards.iter_mut() {
+            *visibility = if snapshot.collected_shards.contains(&entity) {
+                Visibility::Hidden
+            } else {
+                Visibility::Visible
+            };
+        }
+        *self.shard_mods = snapshot.shard_mods.clone();
+        self.current_level.allowed_colors = snapshot.allowed_colors;
+        self.current_level.beam_counts = snapshot.beam_counts;
+        if let Ok(mut inventory) = self.q_inventory.get_single_mut() {
+            inventory.current_color = snapshot.current_color;
+            inventory.sources = snapshot.sources;
+        }
+    }
+}
+
+/// [`System`] that takes the [`LevelStartSnapshot`] when a level is loaded, and restores it
+/// whenever the level is reset. Runs after [`cleanup_light_sources`] has despawned the beams.
+pub fn reset_level_state(
+    mut ev_reset_level: EventReader<ResetLevel>,
+    mut level_start: ResMut<LevelStartSnapshot>,
+    mut level_state: LevelState,
+) {
+    let mut switching = false;
+    for ev in ev_reset_level.read() {
+        switching |= *ev == ResetLevel::Switching;
+    }
+    let snapshot = match level_start.0.as_mut() {
+        Some(snapshot) if !switching => snapshot,
+        _ => level_start.0.insert(level_state.initial_snapshot()),
+    };
+    // the player keeps the color they have selected, see reset_player_on_level_switch
+    snapshot.current_color = level_state
+        .q_inventory
+        .get_single()
+        .ok()
+        .and_then(|inventory| inventory.current_color);
+    level_state.restore(snapshot);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+    use bevy_ecs_ldtk::prelude::*;
+    use enum_map::enum_map;
+
+    use super::*;
+    use crate::level::sensor::SensorMode;
+
+    fn setup() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_resource::<CurrentLevel>()
+            .init_resource::<CrystalShardMods>()
+            .init_resource::<LightSegmentCache>();
+        app.world_mut().resource_mut::<CurrentLevel>().beam_counts = enum_map! {
+            LightColor::Green => 2,
+            _ => 0,
+        };
+        app.world_mut()
+            .spawn(PlayerLightInventory::new(enum_map! { _ => 0 }))
+            .insert(PlayerMarker);
+        app
+    }
+
+    #[test]
+    fn restore_undoes_changes() {
+        let mut app = setup();
+        let world = app.world_mut();
+        let sensor = world
+            .spawn(LightSensor::new(None, 64, -1, -1, SensorMode::Standard, 64))
+            .id();
+        let crystal = world
+            .spawn((
+                Crystal::from(IntGridCell { value: 3 }),
+                TileTextureIndex(10),
+            ))
+            .id();
+
+        let mut state = SystemState::<LevelState>::new(world);
+        let snapshot = state.get_mut(world).snapshot();
+
+        world.get_mut::<LightSensor>(sensor).unwrap().is_active = true;
+        world.get_mut::<LightSensor>(sensor).unwrap().hit_by[LightColor::White] = true;
+        let was_active = world.get::<Crystal>(crystal).unwrap().active;
+        world.get_mut::<Crystal>(crystal).unwrap().active = !was_active;
+        world.resource_mut::<CurrentLevel>().beam_counts[LightColor::Green] = 5;
+
+        state.get_mut(world).restore(&snapshot);
+        state.apply(world);
+
+        let restored = world.get::<LightSensor>(sensor).unwrap();
+        assert!(!restored.is_active);
+        assert!(!restored.hit_by[LightColor::White]);
+        assert_eq!(world.get::<Crystal>(crystal).unwrap().active, was_active);
+        assert_eq!(
+            world.resource::<CurrentLevel>().beam_counts[LightColor::Green],
+            2
+        );
+    }
+
+    #[test]
+    fn initial_snapshot_ignores_changes() {
+        let mut app = setup();
+        let world = app.world_mut();
+        let mut sensor = LightSensor::new(None, 64, -1, -1, SensorMode::Latch, 64);
+        sensor.is_active = true;
+        let sensor = world.spawn(sensor).id();
+        let mut crystal = Crystal::from(IntGridCell { value: 3 });
+        crystal.active = !crystal.init_active;
+        let crystal = world.spawn((crystal, TileTextureIndex(10))).id();
+
+        let mut state = SystemState::<LevelState>::new(world);
+        let snapshot = state.get_mut(world).initial_snapshot();
+        state.get_mut(world).restore(&snapshot);
+        state.apply(world);
+
+        assert!(!world.get::<LightSensor>(sensor).unwrap().is_active);
+        let crystal = world.get::<Crystal>(crystal).unwrap();
+        assert_eq!(crystal.active, crystal.init_active);
+        let inventory = world.query::<&PlayerLightInventory>().single(world);
+        assert_eq!(inventory.sources[LightColor::Green], 2);
+    }
+}
@@ -0,0 +1,368 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use enum_map::{enum_map, EnumMap};
+
+use crate::{
+    camera::HIGHRES_LAYER,
+    config::Config,
+    light::{render::light_beam_mesh, LightBeamSource, LightColor, LightSegmentZMarker},
+    player::light::aim::BeamAim,
+    shared::{GameState, ResetLevel},
+    ui::level_select::Levels,
+};
+
+use super::{CurrentLevel, LevelSystems};
+
+/// Deaths in the same level after which the first step of its solution is shown.
+const SOLUTION_HINT_DEATHS: u32 = 5;
+
+/// [`Plugin`] that shows the reference solution of a level, stored as `SolutionShot` Ldtk
+/// entities, as faint ghost beams one shot at a time.
+pub struct SolutionHintPlugin;
+
+impl Plugin for SolutionHintPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<SolutionShotBundle>("SolutionShot")
+            .init_resource::<SolutionHint>()
+            .init_resource::<SolutionGhostData>()
+            .add_systems(Startup, spawn_solution_ghost)
+            .add_systems(Update, count_solution_deaths.in_set(LevelSystems::Reset))
+            .add_systems(
+                Update,
+                (advance_solution_hint, draw_solution_ghost)
+                    .chain()
+                    .in_set(LevelSystems::Simulation),
+            );
+    }
+}
+
+/// [`Component`] for one shot of a level's reference solution. The shot is fired from the
+/// entity's position.
+#[derive(Component, Debug)]
+pub struct SolutionShot {
+    color: LightColor,
+    /// Direction of the shot
+    direction: Vec2,
+    /// Position of the shot in the solution, from the `step` field
+    step: i32,
+}
+
+impl From<&EntityInstance> for SolutionShot {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let color = entity_instance
+            .get_enum_field("light_color")
+            .expect("light_color needs to be an enum field on all solution shots")
+            .into();
+
+        let direction = entity_instance
+            .get_float_field("direction")
+            .expect("direction needs to be a float field on all solution shots");
+
+        let step = entity_instance
+            .get_int_field("step")
+            .expect("step needs to be an int field on all solution shots");
+
+        SolutionShot {
+            color,
+            direction: Vec2::from_angle(direction.to_radians()),
+            step: *step,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct SolutionShotBundle {
+    #[from_entity_instance]
+    shot: SolutionShot,
+}
+
+/// [`Resource`] holding which step of the current level's solution is shown, if any.
+#[derive(Resource, Debug)]
+pub struct SolutionHint {
+    /// Whether solution hints can be shown, from the settings
+    enabled: bool,
+    /// Index of the shown shot, in step order
+    step: Option<usize>,
+    deaths: u32,
+    /// Whether the solution was already shown because of the deaths in this level
+    shown_for_deaths: bool,
+}
+
+impl Default for SolutionHint {
+    fn default() -> Self {
+        SolutionHint {
+            enabled: true,
+            step: None,
+            deaths: 0,
+            shown_for_deaths: false,
+        }
+    }
+}
+
+impl SolutionHint {
+    /// Turns solution hints on or off, hiding the shown one when turned off.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.step = None;
+        }
+    }
+
+    /// Moves to the next step of a solution with `steps` shots when the hint key was `requested`,
+    /// or shows the first step once the player has died enough times in this level. Pressing the
+    /// key on the last step hides the solution. Returns whether the shown step changed.
+    pub fn advance(&mut self, requested: bool, steps: usize) -> bool {
+        if !self.enabled || steps == 0 {
+            return false;
+        }
+        let died_enough = self.deaths >= SOLUTION_HINT_DEATHS && !self.shown_for_deaths;
+        let step = match self.step {
+            Some(step) if requested => (step + 1 < steps).then_some(step + 1),
+            None if requested || died_enough => Some(0),
+            step => step,
+        };
+        if step == self.step {
+            return false;
+        }
+        self.step = step;
+        self.shown_for_deaths |= died_enough;
+        true
+    }
+}
+
+/// A resource that stores the materials used to draw the solution's ghost beams.
+#[derive(Resource)]
+pub struct SolutionGhostData {
+    path_material_map: EnumMap<LightColor, MeshMaterial2d<ColorMaterial>>,
+    origin_mesh: Mesh2d,
+    origin_material: MeshMaterial2d<ColorMaterial>,
+}
+
+impl FromWorld for SolutionGhostData {
+    fn from_world(world: &mut World) -> Self {
+        let origin_mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Annulus::new(3.0, 4.0))
+            .into();
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let path_material_map: EnumMap<LightColor, MeshMaterial2d<ColorMaterial>> = enum_map! {
+            val => materials
+                .add(val.light_beam_color().with_alpha(0.25))
+                .into(),
+        };
+        let origin_material = materials.add(Color::srgba(1.0, 1.0, 1.0, 0.4)).into();
+
+        SolutionGhostData {
+            path_material_map,
+            origin_mesh,
+            origin_material,
+        }
+    }
+}
+
+/// Marker [`Component`] for the parent of the entities that make up the ghost beam.
+#[derive(Component)]
+pub struct SolutionGhostMarker;
+
+/// Marker [`Component`] for the mesh of the ghost beam.
+#[derive(Component)]
+pub struct SolutionGhostPathMarker;
+
+/// Marker [`Component`] for the ring drawn where the ghost beam is shot from.
+#[derive(Component)]
+pub struct SolutionGhostOriginMarker;
+
+fn spawn_solution_ghost(mut commands: Commands, ghost_data: Res<SolutionGhostData>) {
+    commands
+        .spawn((
+            SolutionGhostMarker,
+            Transform::default(),
+            Visibility::Hidden,
+        ))
+        .with_child((
+            SolutionGhostPathMarker,
+            Mesh2d::default(),
+            ghost_data.path_material_map[LightColor::Green].clone(),
+            Transform::default(),
+            HIGHRES_LAYER,
+        ))
+        .with_child((
+            SolutionGhostOriginMarker,
+            ghost_data.origin_mesh.clone(),
+            ghost_data.origin_material.clone(),
+            Transform::default(),
+            HIGHRES_LAYER,
+        ));
+}
+
+/// The [`SolutionShot`]s of the current level with their positions, in step order.
+fn current_solution<'a>(
+    current_level: &CurrentLevel,
+    q_shots: &'a Query<(&SolutionShot, &GlobalTransform)>,
+) -> Vec<(&'a SolutionShot, Vec2)> {
+    let mut shots: Vec<(&SolutionShot, Vec2)> = q_shots
+        .iter()
+        .map(|(shot, transform)| (shot, transform.translation().truncate()))
+        .filter(|(_, pos)| current_level.level_box.contains(*pos))
+        .collect();
+    shots.sort_by_key(|(shot, _)| shot.step);
+    shots
+}
+
+/// [`System`] that counts the deaths in the current level, and forgets the solution step shown
+/// when the level changes.
+pub fn count_solution_deaths(
+    mut ev_reset_level: EventReader<ResetLevel>,
+    mut solution_hint: ResMut<SolutionHint>,
+) {
+    for ev in ev_reset_level.read() {
+        match ev {
+            ResetLevel::Respawn => solution_hint.deaths += 1,
+            ResetLevel::Switching => {
+                solution_hint.deaths = 0;
+                solution_hint.step = None;
+                solution_hint.shown_for_deaths = false;
+            }
+        }
+    }
+}
+
+/// [`System`] that shows the first step of the solution after [`SOLUTION_HINT_DEATHS`] deaths,
+/// and the next one each time the solution hint key is pressed. Levels whose solution was shown
+/// are marked in the level select.
+pub fn advance_solution_hint(
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Res<Config>,
+    current_level: Res<CurrentLevel>,
+    q_shots: Query<(&SolutionShot, &GlobalTransform)>,
+    mut solution_hint: ResMut<SolutionHint>,
+    mut levels: ResMut<Levels>,
+) {
+    let steps = current_solution(&current_level, &q_shots).len();
+    let requested = keys.just_pressed(config.controls_config.key_solution_hint);
+    if !solution_hint.advance(requested, steps) {
+        return;
+    }
+
+    if solution_hint.step.is_some() {
+        if let Some(level) = levels
+            .0
+            .iter_mut()
+            .find(|level| level.level_iid == current_level.level_iid)
+        {
+            level.hints_used = true;
+        }
+    }
+}
+
+/// [`System`] that draws the shown step of the solution where a beam shot that way would go now.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn draw_solution_ghost(
+    solution_hint: Res<SolutionHint>,
+    current_level: Res<CurrentLevel>,
+    game_state: Res<State<GameState>>,
+    q_shots: Query<(&SolutionShot, &GlobalTransform)>,
+    mut aim: BeamAim,
+    q_light_segment_z: Query<&GlobalTransform, With<LightSegmentZMarker>>,
+    mut q_ghost: Query<&mut Visibility, With<SolutionGhostMarker>>,
+    mut q_path: Query<
+        (
+            &mut Mesh2d,
+            &mut MeshMaterial2d<ColorMaterial>,
+            &mut Transform,
+        ),
+        (
+            With<SolutionGhostPathMarker>,
+            Without<SolutionGhostOriginMarker>,
+        ),
+    >,
+    mut q_origin: Query<&mut Transform, With<SolutionGhostOriginMarker>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    ghost_data: Res<SolutionGhostData>,
+) {
+    let Ok(mut ghost_visibility) = q_ghost.get_single_mut() else {
+        return;
+    };
+    let shots = current_solution(&current_level, &q_shots);
+    let shown = solution_hint
+        .step
+        .filter(|_| *game_state.get() == GameState::Playing)
+        .and_then(|step| shots.get(step));
+    let Some((shot, pos)) = shown else {
+        *ghost_visibility = Visibility::Hidden;
+        return;
+    };
+    let Ok(light_segment_z) = q_light_segment_z.get_single() else {
+        return;
+    };
+
+    let source = LightBeamSource {
+        start_pos: *pos,
+        start_dir: shot.direction,
+        time_traveled: 10000.0,
+        color: shot.color,
+    };
+    let Some(playback) = aim.trace(&source) else {
+        return;
+    };
+    let Some(mesh) = light_beam_mesh(&playback.iter_points(&source).collect::<Vec<_>>()) else {
+        *ghost_visibility = Visibility::Hidden;
+        return;
+    };
+    *ghost_visibility = Visibility::Visible;
+    let z = light_segment_z.translation().z;
+
+    if let Ok((mut mesh_2d, mut material, mut path_transform)) = q_path.get_single_mut() {
+        match meshes.get_mut(&mesh_2d.0) {
+            Some(old_mesh) => *old_mesh = mesh,
+            None => mesh_2d.0 = meshes.add(mesh),
+        }
+        *material = ghost_data.path_material_map[shot.color].clone();
+        path_transform.translation.z = z;
+    }
+    if let Ok(mut origin_transform) = q_origin.get_single_mut() {
+        origin_transform.translation = pos.extend(z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_steps_through_then_hides() {
+        let mut hint = SolutionHint::default();
+        assert!(!hint.advance(false, 2));
+        assert!(hint.advance(true, 2));
+        assert_eq!(hint.step, Some(0));
+        assert!(hint.advance(true, 2));
+        assert_eq!(hint.step, Some(1));
+        assert!(hint.advance(true, 2));
+        assert_eq!(hint.step, None);
+    }
+
+    #[test]
+    fn deaths_show_solution_once() {
+        let mut hint = SolutionHint {
+            deaths: SOLUTION_HINT_DEATHS,
+            ..default()
+        };
+        assert!(hint.advance(false, 2));
+        assert_eq!(hint.step, Some(0));
+        assert!(hint.advance(true, 1));
+        assert_eq!(hint.step, None);
+        assert!(!hint.advance(false, 1));
+        assert_eq!(hint.step, None);
+    }
+
+    #[test]
+    fn disabled_or_empty_never_shows() {
+        let mut hint = SolutionHint::default();
+        hint.set_enabled(false);
+        assert!(!hint.advance(true, 2));
+        hint.set_enabled(true);
+        assert!(!hint.advance(true, 0));
+        assert_eq!(hint.step, None);
+    }
+}
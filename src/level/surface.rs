@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+use serde::Deserialize;
+
+/// [`Plugin`] that loads the [`SurfaceTable`]. The table is embedded at compile time so it is
+/// also there on the web build.
+pub struct SurfacePlugin;
+
+impl Plugin for SurfacePlugin {
+    fn build(&self, app: &mut App) {
+        let table: SurfaceTable = toml::from_str(include_str!("../../assets/surfaces.toml"))
+            .expect("Failed to parse surfaces.toml");
+        app.insert_resource(table);
+    }
+}
+
+/// The `Terrain` int grid value of walls, which are merged before they get their [`Surface`].
+pub const WALL_INT_GRID_VALUE: i32 = 1;
+
+/// [`Component`] for what a collider is made of, used to look up how light beams interact with
+/// it in the [`SurfaceTable`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Surface {
+    /// Terrain made of the given `Terrain` int grid value
+    Terrain(i32),
+    /// Moving platforms
+    Platform,
+    /// White light beams, which other beams bounce off of
+    WhiteBeam,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Surface::Terrain(WALL_INT_GRID_VALUE)
+    }
+}
+
+impl From<IntGridCell> for Surface {
+    fn from(cell_instance: IntGridCell) -> Self {
+        Surface::Terrain(cell_instance.value)
+    }
+}
+
+/// How a light beam behaves when it hits a [`Surface`].
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceMaterial {
+    /// Reflects the beam, using up one of its bounces
+    #[default]
+    Specular,
+    /// Reflects the beam without using up a bounce
+    Mirror,
+    /// Ends the beam
+    Absorbing,
+    /// Rough surface that ends the beam, which glows where it hits
+    Diffuse,
+}
+
+/// How a light beam behaves and sounds when it hits a [`Surface`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SurfaceInfo {
+    pub material: SurfaceMaterial,
+    /// Path of the sound played when a beam hits the surface, where `{bounce}` is replaced by the
+    /// number of the bounce, from 1 to 3
+    pub sound: String,
+    /// Playback speed of the sound
+    pub speed: f32,
+}
+
+impl Default for SurfaceInfo {
+    fn default() -> Self {
+        SurfaceInfo {
+            material: SurfaceMaterial::Specular,
+            sound: "sfx/light/light-bounce-{bounce}.wav".into(),
+            speed: 1.0,
+        }
+    }
+}
+
+impl SurfaceInfo {
+    /// Path of the sound played on the given bounce, from 1 to 3.
+    pub fn sound_path(&self, bounce: usize) -> String {
+        self.sound.replace("{bounce}", &bounce.to_string())
+    }
+}
+
+/// [`Resource`] holding the [`SurfaceInfo`] of each [`Surface`], read from
+/// `assets/surfaces.toml`.
+#[derive(Deserialize, Resource, Debug, Default)]
+#[serde(default)]
+pub struct SurfaceTable {
+    /// Name of the surface each `Terrain` int grid value is made of. TOML keys are always
+    /// strings, so the values are stored as text.
+    terrain: HashMap<String, String>,
+    /// Surfaces by name
+    surfaces: HashMap<String, SurfaceInfo>,
+    /// Used for colliders that don't have a [`Surface`], or whose surface isn't in the table
+    default: SurfaceInfo,
+}
+
+impl SurfaceTable {
+    pub fn get(&self, surface: Surface) -> &SurfaceInfo {
+        let name = match surface {
+            Surface::Terrain(value) => self.terrain.get(&value.to_string()).map(String::as_str),
+            Surface::Platform => Some("platform"),
+            Surface::WhiteBeam => Some("white_beam"),
+        };
+        name.and_then(|name| self.surfaces.get(name))
+            .unwrap_or(&self.default)
+    }
+
+    /// Iterates over every [`SurfaceInfo`] in the table, including the default one.
+    pub fn iter(&self) -> impl Iterator<Item = &SurfaceInfo> {
+        self.surfaces.values().chain([&self.default])
+    }
+}
+
+/// [`SystemParam`] used to look up the [`SurfaceInfo`] of a collider hit by a light beam.
+/// Colliders without a [`Surface`] (sensors, white beams) use the table's default.
+#[derive(SystemParam)]
+pub struct SurfaceMaterials<'w, 's> {
+    q_surfaces: Query<'w, 's, &'static Surface>,
+    table: Res<'w, SurfaceTable>,
+}
+
+impl SurfaceMaterials<'_, '_> {
+    pub fn get(&self, entity: Entity) -> SurfaceMaterial {
+        self.info(entity).material
+    }
+
+    pub fn info(&self, entity: Entity) -> &SurfaceInfo {
+        match self.q_surfaces.get(entity) {
+            Ok(surface) => self.table.get(*surface),
+            Err(_) => &self.table.default,
+        }
+    }
+
+    pub fn table(&self) -> &SurfaceTable {
+        &self.table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surfaces_toml_maps_terrain() {
+        let table: SurfaceTable =
+            toml::from_str(include_str!("../../assets/surfaces.toml")).unwrap();
+
+        assert_eq!(
+            table.get(Surface::Terrain(16)).material,
+            SurfaceMaterial::Mirror
+        );
+        assert_eq!(
+            table.get(Surface::Terrain(2)),
+            table.surfaces.get("spike").unwrap()
+        );
+        assert_eq!(table.get(Surface::Terrain(-1)), &table.default);
+        assert_eq!(
+            table.get(Surface::WhiteBeam).sound_path(2),
+            "sfx/light/light-bounce-2-reflect.wav"
+        );
+    }
+}
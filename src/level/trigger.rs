@@ -0,0 +1,347 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    camera::{CameraControlType, CameraFocus, CameraZoomEvent},
+    cutscene::StartCutsceneEvent,
+    player::{kill::KillPlayerEvent, PlayerHurtMarker},
+    shared::GroupLabel,
+    sound::{BgmTrack, ChangeBgmEvent},
+    ui::hint::{Hint, ShowHintEvent},
+};
+
+use super::{
+    crystal::{CrystalColor, CrystalIdent, CrystalToggleEvent},
+    platform::{ChangePlatformStateEvent, PlatformState},
+    CurrentLevel, LevelSystems,
+};
+
+/// Cutscene used by `dialogue` actions, which locks Lyra in place while the dialogue plays.
+const DIALOGUE_CUTSCENE: &str = "dialogue";
+
+/// Seconds a `zoom` action takes when it doesn't say.
+const DEFAULT_ZOOM_SECS: f32 = 0.5;
+
+/// [`Plugin`] for the `Trigger` Ldtk entity, an area that runs a list of actions when Lyra enters
+/// it.
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<TriggerBundle>("Trigger")
+            .add_systems(Update, reset_triggers.in_set(LevelSystems::Reset))
+            .add_systems(
+                FixedUpdate,
+                fire_triggers
+                    .after(PhysicsSet::Writeback)
+                    .in_set(LevelSystems::Simulation),
+            );
+    }
+}
+
+/// Something a [`Trigger`] does when Lyra enters it. Each one is written in the `actions` string
+/// array field of the Ldtk entity as the action's name followed by its arguments:
+/// - `dialogue <id>`: plays the dialogue with this id
+/// - `bgm <track>`: switches the music to this [`BgmTrack`] until the level changes
+/// - `camera <x> <y>`: makes the camera frame this point of the level, in Ldtk pixel coordinates
+/// - `camera lyra`: makes the camera follow Lyra again
+/// - `zoom <scale> [seconds]`: zooms the camera to this scale
+/// - `crystal <color> <id>`: toggles the crystals of this color and id
+/// - `platform <id> <Play|Pause|Stop>`: changes the state of the platforms with this id
+/// - `hint <id>`: shows this tutorial [`Hint`], unless the player has seen it
+/// - `kill`: kills Lyra
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerAction {
+    Dialogue(String),
+    Bgm(BgmTrack),
+    CameraFocus(Option<Vec2>),
+    Zoom { scale: f32, seconds: f32 },
+    Crystal(CrystalIdent),
+    Platform { id: i32, state: PlatformState },
+    Hint(Hint),
+    Kill,
+}
+
+impl From<&str> for TriggerAction {
+    fn from(value: &str) -> Self {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        let number = |i: usize| -> f32 {
+            words
+                .get(i)
+                .and_then(|word| word.parse().ok())
+                .unwrap_or_else(|| panic!("Trigger action {value} needs a number at position {i}"))
+        };
+
+        match words.as_slice() {
+            ["dialogue", id] => TriggerAction::Dialogue(id.to_string()),
+            ["bgm", track] => TriggerAction::Bgm(match *track {
+                "MustntStop" => BgmTrack::MustntStop,
+                "LightInTheDark" => BgmTrack::LightInTheDark,
+                "Cutscene1Draft" => BgmTrack::Cutscene1Draft,
+                "LevelSelect" => BgmTrack::LevelSelect,
+                "None" => BgmTrack::None,
+                _ => panic!("String {track} does not represent a BgmTrack"),
+            }),
+            ["camera", "lyra"] => TriggerAction::CameraFocus(None),
+            ["camera", _, _] => TriggerAction::CameraFocus(Some(Vec2::new(number(1), number(2)))),
+            ["zoom", _] => TriggerAction::Zoom {
+                scale: number(1),
+                seconds: DEFAULT_ZOOM_SECS,
+            },
+            ["zoom", _, _] => TriggerAction::Zoom {
+                scale: number(1),
+                seconds: number(2),
+            },
+            ["crystal", color, _] => TriggerAction::Crystal(CrystalIdent {
+                color: CrystalColor::from(&color.to_string()),
+                id: number(2) as i32,
+            }),
+            ["platform", _, state] => TriggerAction::Platform {
+                id: number(1) as i32,
+                state: match *state {
+                    "Play" => PlatformState::Play,
+                    "Pause" => PlatformState::Pause,
+                    "Stop" => PlatformState::Stop,
+                    _ => panic!("String {state} does not represent a PlatformState"),
+                },
+            },
+            ["hint", id] => TriggerAction::Hint(Hint::from(*id)),
+            ["kill"] => TriggerAction::Kill,
+            _ => panic!("String {value} does not represent a TriggerAction"),
+        }
+    }
+}
+
+/// [`Component`] for an area that runs its [`TriggerAction`]s when Lyra enters it.
+#[derive(Component, Debug)]
+pub struct Trigger {
+    actions: Vec<TriggerAction>,
+    /// Whether the trigger fires every time Lyra enters it, instead of once per level attempt
+    repeat: bool,
+    fired: bool,
+    /// Whether Lyra was inside the area last tick
+    occupied: bool,
+}
+
+impl From<&EntityInstance> for Trigger {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let actions = entity_instance
+            .get_maybe_strings_field("actions")
+            .expect("actions needs to be a string array field on all triggers")
+            .iter()
+            .flatten()
+            .map(|action| TriggerAction::from(action.as_str()))
+            .collect();
+
+        let repeat = entity_instance
+            .get_bool_field("repeat")
+            .copied()
+            .unwrap_or(false);
+
+        Trigger {
+            actions,
+            repeat,
+            fired: false,
+            occupied: false,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TriggerBundle {
+    trigger: Trigger,
+    collider: Collider,
+    sensor: Sensor,
+    collision_groups: CollisionGroups,
+}
+
+impl LdtkEntity for TriggerBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        Self {
+            trigger: Trigger::from(entity_instance),
+            collider: Collider::cuboid(
+                (entity_instance.width / 2) as f32,
+                (entity_instance.height / 2) as f32,
+            ),
+            sensor: Sensor,
+            collision_groups: CollisionGroups::new(
+                GroupLabel::ALL,
+                GroupLabel::PLAYER_COLLIDER | GroupLabel::PLAYER_SENSOR,
+            ),
+        }
+    }
+}
+
+/// [`System`] that runs the actions of the [`Trigger`]s Lyra just entered.
+#[allow(clippy::too_many_arguments)]
+pub fn fire_triggers(
+    rapier_context: Query<&RapierContext>,
+    q_player: Query<Entity, With<PlayerHurtMarker>>,
+    mut q_triggers: Query<(Entity, &mut Trigger)>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut camera_focus: ResMut<CameraFocus>,
+    mut ev_start_cutscene: EventWriter<StartCutsceneEvent>,
+    mut ev_change_bgm: EventWriter<ChangeBgmEvent>,
+    mut ev_zoom_camera: EventWriter<CameraZoomEvent>,
+    mut ev_crystal_toggle: EventWriter<CrystalToggleEvent>,
+    mut ev_platform_change: EventWriter<ChangePlatformStateEvent>,
+    mut ev_show_hint: EventWriter<ShowHintEvent>,
+    mut ev_kill_player: EventWriter<KillPlayerEvent>,
+) {
+    let (Ok(rapier_context), Ok(player_entity)) =
+        (rapier_context.get_single(), q_player.get_single())
+    else {
+        return;
+    };
+
+    for (trigger_entity, mut trigger) in q_triggers.iter_mut() {
+        let occupied = rapier_context
+            .intersection_pair(trigger_entity, player_entity)
+            .unwrap_or(false);
+        let entered = occupied && !trigger.occupied;
+        trigger.occupied = occupied;
+        if !entered || (trigger.fired && !trigger.repeat) {
+            continue;
+        }
+        trigger.fired = true;
+
+        for action in trigger.actions.iter() {
+            match action {
+                TriggerAction::Dialogue(id) => {
+                    ev_start_cutscene.send(StartCutsceneEvent {
+                        id: DIALOGUE_CUTSCENE.to_string(),
+                        dialogue: Some(id.clone()),
+                    });
+                }
+                TriggerAction::Bgm(track) => {
+                    current_level.bgm = Some(*track);
+                    ev_change_bgm.send(ChangeBgmEvent(*track));
+                }
+                TriggerAction::CameraFocus(point) => {
+                    // Ldtk positions are from the top left of the level, with y pointing down
+                    let level_box = current_level.level_box;
+                    camera_focus.0 = point.map(|point| {
+                        Vec2::new(level_box.min.x + point.x, level_box.max.y - point.y)
+                    });
+                }
+                TriggerAction::Zoom { scale, seconds } => {
+                    ev_zoom_camera.send(CameraZoomEvent {
+                        scale: *scale,
+                        variant: CameraControlType::Animated {
+                            duration: Duration::from_secs_f32(*seconds),
+                            ease_fn: EaseFunction::SineInOut,
+                            callback: None,
+                        },
+                    });
+                }
+                TriggerAction::Crystal(ident) => {
+                    ev_crystal_toggle.send(CrystalToggleEvent { color: *ident });
+                }
+                TriggerAction::Platform { id, state } => {
+                    ev_platform_change.send(ChangePlatformStateEvent {
+                        id: *id,
+                        new_state: *state,
+                    });
+                }
+                TriggerAction::Hint(hint) => {
+                    ev_show_hint.send(ShowHintEvent(*hint));
+                }
+                TriggerAction::Kill => {
+                    ev_kill_player.send(KillPlayerEvent);
+                }
+            }
+        }
+    }
+}
+
+/// [`System`] that rearms the [`Trigger`]s and puts the camera back on Lyra when the level resets.
+pub fn reset_triggers(
+    mut q_triggers: Query<&mut Trigger>,
+    mut camera_focus: ResMut<CameraFocus>,
+    mut ev_zoom_camera: EventWriter<CameraZoomEvent>,
+) {
+    let mut zoomed = false;
+    for mut trigger in q_triggers.iter_mut() {
+        zoomed |= trigger.fired
+            && trigger
+                .actions
+                .iter()
+                .any(|action| matches!(action, TriggerAction::Zoom { .. }));
+        trigger.fired = false;
+        trigger.occupied = false;
+    }
+    camera_focus.0 = None;
+    if zoomed {
+        ev_zoom_camera.send(CameraZoomEvent {
+            scale: 1.,
+            variant: CameraControlType::Instant,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions() {
+        assert_eq!(
+            TriggerAction::from("dialogue shard_green"),
+            TriggerAction::Dialogue("shard_green".to_string())
+        );
+        assert_eq!(
+            TriggerAction::from("bgm  LightInTheDark"),
+            TriggerAction::Bgm(BgmTrack::LightInTheDark)
+        );
+        assert_eq!(
+            TriggerAction::from("camera 120 -8.5"),
+            TriggerAction::CameraFocus(Some(Vec2::new(120., -8.5)))
+        );
+        assert_eq!(
+            TriggerAction::from("camera lyra"),
+            TriggerAction::CameraFocus(None)
+        );
+        assert_eq!(
+            TriggerAction::from("zoom 0.75"),
+            TriggerAction::Zoom {
+                scale: 0.75,
+                seconds: DEFAULT_ZOOM_SECS
+            }
+        );
+        assert_eq!(
+            TriggerAction::from("crystal Red 3"),
+            TriggerAction::Crystal(CrystalIdent {
+                color: CrystalColor::Red,
+                id: 3
+            })
+        );
+        assert_eq!(
+            TriggerAction::from("platform 2 Pause"),
+            TriggerAction::Platform {
+                id: 2,
+                state: PlatformState::Pause
+            }
+        );
+        assert_eq!(
+            TriggerAction::from("hint drop_through"),
+            TriggerAction::Hint(Hint::DropThrough)
+        );
+        assert_eq!(TriggerAction::from("kill"), TriggerAction::Kill);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_unknown_actions() {
+        let _ = TriggerAction::from("explode");
+    }
+}
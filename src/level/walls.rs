@@ -4,7 +4,10 @@ use bevy_rapier2d::prelude::*;
 
 use crate::{lighting::Occluder2d, particle::dust::DustSurface, shared::GroupLabel};
 
-use super::merge_tile::MergedTile;
+use super::{
+    merge_tile::MergedTile,
+    surface::{Surface, WALL_INT_GRID_VALUE},
+};
 
 /// Marker [`Component`] representing a wall.
 #[derive(Default, Component)]
@@ -40,6 +43,7 @@ impl MergedTile for Wall {
             RigidBody::Fixed,
             Transform::from_xyz(center.x, center.y, 0.),
             DustSurface::Wall,
+            Surface::Terrain(WALL_INT_GRID_VALUE),
         ));
     }
 
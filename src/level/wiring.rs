@@ -0,0 +1,125 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ecs_ldtk::{prelude::*, EntityIid};
+
+use super::{entity_level_iid, LevelSystems};
+
+/// [`Plugin`] that lets Ldtk entities reference each other with `EntityRef` fields. Entities with
+/// a [`WireTargets`] component send a [`WireSignalEvent`] to each of their targets when they
+/// turn on or off, and each kind of target decides how to react to it.
+pub struct WiringPlugin;
+
+impl Plugin for WiringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WireSignalEvent>()
+            .init_resource::<EntityIidCache>()
+            .add_systems(
+                PreUpdate,
+                (
+                    invalidate_entity_iid_cache,
+                    init_entity_iid_cache.ambiguous_with(invalidate_entity_iid_cache),
+                )
+                    .in_set(LevelSystems::Processing),
+            );
+    }
+}
+
+/// Event sent by a wired entity (for example a [`LightSensor`](super::sensor::LightSensor)) to
+/// one of its [`WireTargets`] when it is activated or deactivated.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WireSignalEvent {
+    pub target: Entity,
+    pub active: bool,
+}
+
+/// [`Resource`] mapping the iid of each spawned Ldtk entity to its [`Entity`], grouped by level so
+/// that the entries of a level can be dropped when it despawns.
+#[derive(Debug, Default, Resource)]
+pub struct EntityIidCache {
+    entities: HashMap<LevelIid, HashMap<EntityIid, Entity>>,
+}
+
+impl EntityIidCache {
+    pub fn get(&self, level_iid: &LevelIid, entity_iid: &EntityIid) -> Option<Entity> {
+        self.entities.get(level_iid)?.get(entity_iid).copied()
+    }
+}
+
+fn invalidate_entity_iid_cache(
+    mut ev_level: EventReader<LevelEvent>,
+    mut entity_iid_cache: ResMut<EntityIidCache>,
+) {
+    for ev in ev_level.read() {
+        let LevelEvent::Despawned(iid) = ev else {
+            continue;
+        };
+        if let Some(mp) = entity_iid_cache.entities.get_mut(iid) {
+            mp.clear();
+        }
+    }
+}
+
+fn init_entity_iid_cache(
+    q_entities: Query<(Entity, &EntityIid), Added<EntityIid>>,
+    q_parents: Query<&Parent>,
+    q_level_iid: Query<&LevelIid>,
+    mut entity_iid_cache: ResMut<EntityIidCache>,
+) {
+    for (entity, entity_iid) in q_entities.iter() {
+        let Some(level_iid) = entity_level_iid(entity, &q_parents, &q_level_iid) else {
+            continue;
+        };
+        entity_iid_cache
+            .entities
+            .entry(level_iid.clone())
+            .or_default()
+            .insert(entity_iid.clone(), entity);
+    }
+}
+
+/// [`Component`] holding the entities referenced by the optional `targets` entity reference array
+/// field of an Ldtk entity. Only Ldtk entities can be referenced, so int grid tiles like crystals
+/// and mirrors still have to be wired through their ids.
+#[derive(Component, Default, Debug, Clone)]
+pub struct WireTargets(Vec<(LevelIid, EntityIid)>);
+
+impl WireTargets {
+    /// Iterates over the targets that are currently spawned.
+    pub fn resolve<'a>(&'a self, cache: &'a EntityIidCache) -> impl Iterator<Item = Entity> + 'a {
+        self.0
+            .iter()
+            .filter_map(|(level_iid, entity_iid)| cache.get(level_iid, entity_iid))
+    }
+
+    /// Sends a [`WireSignalEvent`] to each of the targets.
+    pub fn send(
+        &self,
+        active: bool,
+        cache: &EntityIidCache,
+        ev_wire_signal: &mut EventWriter<WireSignalEvent>,
+    ) {
+        for target in self.resolve(cache) {
+            ev_wire_signal.send(WireSignalEvent { target, active });
+        }
+    }
+}
+
+impl From<&EntityInstance> for WireTargets {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let Ok(targets) = entity_instance.get_maybe_entity_refs_field("targets") else {
+            return WireTargets::default();
+        };
+
+        WireTargets(
+            targets
+                .iter()
+                .flatten()
+                .map(|target| {
+                    (
+                        LevelIid::new(target.level_iid.clone()),
+                        EntityIid::new(target.entity_iid.clone()),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
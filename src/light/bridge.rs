@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    level::CurrentLevel,
+    player::{movement::PlayerMovement, PlayerMarker},
+    shared::GroupLabel,
+};
+
+use super::segments::LightSegment;
+
+/// Half of the thickness of the [`Collider`] that makes a light segment walkable.
+pub const LIGHT_BRIDGE_HALF_THICKNESS: f32 = 1.5;
+
+/// Segments steeper than this (the y component of their upwards normal) are never walkable.
+const LIGHT_BRIDGE_MIN_NORMAL_Y: f32 = 0.5;
+
+/// Marker [`Component`] for the child of each [`LightSegment`] that carries its one-way bridge
+/// [`Collider`]. The collider only collides with the player when the segment's color is solid in
+/// the current level (see [`CurrentLevel::solid_colors`]) and the player is above it, just like
+/// semisolid platforms.
+#[derive(Default, Component)]
+pub struct LightBridge;
+
+/// Returns the [`Bundle`] for the bridge child of a light segment. The collider is scaled along
+/// with the segment, so it is a unit square stretched over the segment's length.
+pub fn light_bridge_bundle() -> impl Bundle {
+    (
+        LightBridge,
+        Collider::cuboid(0.5, LIGHT_BRIDGE_HALF_THICKNESS),
+        CollisionGroups::new(GroupLabel::TERRAIN, Group::NONE),
+        Transform::default(),
+    )
+}
+
+/// [`System`] that makes the [`LightBridge`]s of solid segments collide with the player when the
+/// player is above them. Runs after
+/// [`visually_sync_segments`](super::segments::visually_sync_segments) so that the bridges follow
+/// the segments.
+pub fn update_light_bridges(
+    current_level: Res<CurrentLevel>,
+    q_player: Query<(&PlayerMovement, &GlobalTransform), With<PlayerMarker>>,
+    q_segments: Query<(&LightSegment, &Transform, &Visibility)>,
+    mut q_bridges: Query<(&Parent, &mut CollisionGroups), With<LightBridge>>,
+) {
+    let Ok((movement, player)) = q_player.get_single() else {
+        return;
+    };
+    let cutoff_height = movement.one_way_cutoff();
+    let player_pos = player.translation().xy();
+
+    for (parent, mut collisions) in q_bridges.iter_mut() {
+        let Ok((segment, transform, visibility)) = q_segments.get(parent.get()) else {
+            continue;
+        };
+
+        let dir = (transform.rotation * Vec3::X).xy();
+        let mut up = dir.perp();
+        if up.y < 0.0 {
+            up = -up;
+        }
+
+        let solid = current_level.solid_colors[segment.color]
+            && *visibility != Visibility::Hidden
+            && up.y >= LIGHT_BRIDGE_MIN_NORMAL_Y
+            && (player_pos - transform.translation.xy()).dot(up) > cutoff_height;
+
+        let filter = if solid {
+            GroupLabel::PLAYER_COLLIDER
+        } else {
+            Group::NONE
+        };
+        if collisions.filters != filter {
+            *collisions = CollisionGroups::new(GroupLabel::TERRAIN, filter);
+        }
+    }
+}
@@ -1,26 +1,34 @@
 use bevy::{
+    diagnostic::{Diagnostic, RegisterDiagnostic},
+    ecs::query::QueryFilter,
     prelude::*,
     sprite::{AlphaMode2d, Material2dPlugin},
 };
 use bevy_ecs_ldtk::prelude::*;
 
+use bridge::update_light_bridges;
 use enum_map::Enum;
 use render::{LightMaterial, LightRenderData};
 use segments::{
-    cleanup_light_sources, simulate_light_sources, spawn_needed_segments, tick_light_sources,
-    visually_sync_segments, LightSegmentCache, PrevLightBeamPlayback,
+    cleanup_light_sources, despawn_light_beam, simulate_light_sources, spawn_needed_segments,
+    sync_beam_glows, tick_light_sources, visually_sync_segments, LightSegmentCache,
+    PrevLightBeamPlayback, LIGHT_SIMULATION_TIME, TRACED_LIGHT_BEAMS,
 };
 
-use crate::{level::LevelSystems, lighting::LineLight2d};
+use crate::{
+    level::{sensor::LightSensor, wiring::WireSignalEvent, LevelSystems},
+    lighting::LineLight2d,
+};
 
-mod render;
+pub mod bridge;
+pub mod render;
 pub mod segments;
 
 /// The speed of the light beam in units per [`FixedUpdate`].
-const LIGHT_SPEED: f32 = 8.0;
+pub const LIGHT_SPEED: f32 = 8.0;
 const BLOCK_WIDTH: f32 = 8.0;
 
-/// The width of the rectangle used to represent [`LightSegment`](segments::LightSegmentBundle)s.
+/// The width of the mesh used to draw light beams.
 const LIGHT_SEGMENT_THICKNESS: f32 = 3.0;
 
 /// [`Plugin`] that manages everything light related.
@@ -31,6 +39,8 @@ impl Plugin for LightManagementPlugin {
         app.add_plugins(Material2dPlugin::<LightMaterial>::default())
             .init_resource::<LightRenderData>()
             .init_resource::<LightSegmentCache>()
+            .register_diagnostic(Diagnostic::new(LIGHT_SIMULATION_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(TRACED_LIGHT_BEAMS))
             .register_ldtk_entity::<LightSegmentZBundle>("LightSegmentZMarker")
             .register_ldtk_entity::<LightSourceZBundle>("LightSourceZMarker")
             .register_ldtk_entity::<LightSourceBundle>("LightSource")
@@ -41,12 +51,21 @@ impl Plugin for LightManagementPlugin {
                         simulate_light_sources,
                         spawn_needed_segments,
                         visually_sync_segments,
+                        update_light_bridges,
+                        sync_beam_glows,
                     )
                         .chain(),
                     tick_light_sources,
                 )
                     .in_set(LevelSystems::Simulation),
             )
+            .add_systems(
+                FixedUpdate,
+                toggle_wired_emitters
+                    .before(simulate_light_sources)
+                    .in_set(LevelSystems::Simulation)
+                    .run_if(on_event::<WireSignalEvent>),
+            )
             // why does this need to be on update???
             .add_systems(Update, cleanup_light_sources.in_set(LevelSystems::Reset))
             .add_systems(
@@ -79,7 +98,10 @@ fn spawn_level_light_beams(
         Added<LightBeamSourceAdded>,
     >,
 ) {
-    for (_, source, transform) in ldtk_sources.iter() {
+    for (emitter, source, transform) in ldtk_sources.iter() {
+        if !source.enabled {
+            continue;
+        }
         let ray_dir_int = source.direction - source.position;
         let ray_dir = Vec2::new(
             ray_dir_int.x as f32 + (source.x_offset / 8.0),
@@ -110,6 +132,7 @@ fn spawn_level_light_beams(
         commands
             .spawn(light_beam_source)
             .insert(PrevLightBeamPlayback::default())
+            .insert(EmitterBeam(emitter))
             .insert(LineLight2d::point(
                 shoot_color.lighting_color().extend(1.0),
                 30.0,
@@ -125,6 +148,7 @@ fn spawn_level_light_beams(
             commands
                 .spawn(light_beam_source)
                 .insert(PrevLightBeamPlayback::default())
+                .insert(EmitterBeam(emitter))
                 .insert(LineLight2d::point(
                     shoot_color.lighting_color().extend(1.0),
                     30.0,
@@ -134,9 +158,92 @@ fn spawn_level_light_beams(
     }
 }
 
+/// Turns a [`LightBeamLDTKSource`] on or off, spawning or despawning the beams it emits.
+pub fn set_emitter_enabled<F: QueryFilter>(
+    commands: &mut Commands,
+    emitter_entity: Entity,
+    emitter: &mut LightBeamLDTKSource,
+    enabled: bool,
+    q_beams: &Query<
+        (
+            Entity,
+            &LightBeamSource,
+            &PrevLightBeamPlayback,
+            &EmitterBeam,
+        ),
+        F,
+    >,
+    q_light_sensor: &mut Query<&mut LightSensor>,
+    segment_cache: &mut LightSegmentCache,
+) {
+    if emitter.enabled == enabled {
+        return;
+    }
+    emitter.enabled = enabled;
+
+    if enabled {
+        // re-adding the marker makes spawn_level_light_beams spawn the beams again
+        commands
+            .entity(emitter_entity)
+            .remove::<LightBeamSourceAdded>()
+            .insert(LightBeamSourceAdded);
+        return;
+    }
+
+    for (beam_entity, beam, prev_playback, beam_emitter) in q_beams.iter() {
+        if beam_emitter.0 == emitter_entity {
+            despawn_light_beam(
+                commands,
+                beam_entity,
+                beam,
+                prev_playback,
+                q_light_sensor,
+                segment_cache,
+            );
+        }
+    }
+}
+
+/// [`System`] that switches a [`LightBeamLDTKSource`] away from its initial state while a wire
+/// targeting it is active, and back to its initial state when the wire turns off.
+fn toggle_wired_emitters(
+    mut commands: Commands,
+    mut ev_wire_signal: EventReader<WireSignalEvent>,
+    mut q_emitters: Query<&mut LightBeamLDTKSource>,
+    q_beams: Query<(
+        Entity,
+        &LightBeamSource,
+        &PrevLightBeamPlayback,
+        &EmitterBeam,
+    )>,
+    mut q_light_sensor: Query<&mut LightSensor>,
+    mut segment_cache: ResMut<LightSegmentCache>,
+) {
+    for ev in ev_wire_signal.read() {
+        let Ok(mut emitter) = q_emitters.get_mut(ev.target) else {
+            continue;
+        };
+        let enabled = ev.active != emitter.init_enabled;
+        set_emitter_enabled(
+            &mut commands,
+            ev.target,
+            &mut emitter,
+            enabled,
+            &q_beams,
+            &mut q_light_sensor,
+            &mut segment_cache,
+        );
+    }
+}
+
 #[derive(Default, Component)]
 pub struct BlackRayComponent;
 
+/// [`Component`] on the [`LightBeamSource`]s spawned by a [`LightBeamLDTKSource`], pointing back
+/// to it.
+#[derive(Component)]
+pub struct EmitterBeam(pub Entity);
+
 #[derive(Default, Component)]
 pub struct LightBeamSourceAdded;
 
@@ -166,6 +273,10 @@ pub struct LightBeamLDTKSource {
     pub x_offset: f32,
     pub y_offset: f32,
     pub both_directions: bool,
+    /// Whether the source emits when the level starts, from the optional `starts_on` field
+    pub init_enabled: bool,
+    /// Whether the source is currently emitting. Wires targeting the source flip this.
+    pub enabled: bool,
 }
 
 impl From<&bevy_ecs_ldtk::EntityInstance> for LightBeamLDTKSource {
@@ -179,12 +290,18 @@ impl From<&bevy_ecs_ldtk::EntityInstance> for LightBeamLDTKSource {
         let y_offset = *entity_instance.get_float_field("YOffset").unwrap();
         let direction = *entity_instance.get_point_field("Direction").unwrap();
         let both_directions = *entity_instance.get_bool_field("BothDirections").unwrap();
+        let init_enabled = entity_instance
+            .get_bool_field("starts_on")
+            .copied()
+            .unwrap_or(true);
         LightBeamLDTKSource {
             direction,
             position,
             x_offset,
             y_offset,
             both_directions,
+            init_enabled,
+            enabled: init_enabled,
         }
     }
 }
@@ -284,7 +401,7 @@ impl LightColor {
 /// A [`Component`] marking the start of a light ray. These are spawned in
 /// [`shoot_light`](crate::player::light::shoot_light), and simulated in
 /// [`simulate_light_sources`]
-#[derive(Component)]
+#[derive(Component, Clone, Debug)]
 #[require(Transform, Visibility, Sprite, PrevLightBeamPlayback)]
 pub struct LightBeamSource {
     pub start_pos: Vec2,
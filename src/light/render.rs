@@ -1,6 +1,10 @@
 use bevy::{
     prelude::*,
-    render::render_resource::{AsBindGroup, ShaderRef},
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, ShaderRef},
+    },
     sprite::{AlphaMode2d, Material2d},
 };
 use enum_map::{enum_map, EnumMap};
@@ -10,26 +14,22 @@ use super::{LightColor, LIGHT_SEGMENT_THICKNESS};
 /// The path to the shader used by the [`LightMaterial`]
 const LIGHT_SHADER_PATH: &str = "shaders/light.wgsl";
 
-/// A resource that stored handles to the [`Mesh2d`] and [`MeshMaterial2d`] used in the rendering
-/// of [`LightSegment`](super::segments::LightSegmentBundle)s.
+/// How far a mitred joint can stick out, relative to half of [`LIGHT_SEGMENT_THICKNESS`]. Beams
+/// that bounce almost straight back would otherwise get very long spikes.
+const LIGHT_MITER_LIMIT: f32 = 4.0;
+
+/// A resource that stored handles to the [`MeshMaterial2d`]s used in the rendering of light beams.
 #[derive(Resource)]
 pub struct LightRenderData {
-    pub mesh: Mesh2d,
     pub material_map: EnumMap<LightColor, MeshMaterial2d<LightMaterial>>,
 }
 
 impl FromWorld for LightRenderData {
     /// Initializes the [`LightRenderData`] [`Resource`] from the [`World`], before the app runs.
     fn from_world(world: &mut World) -> Self {
-        let mut meshes = world.resource_mut::<Assets<Mesh>>();
-        let mesh_handle = meshes
-            .add(Rectangle::new(1.0, LIGHT_SEGMENT_THICKNESS))
-            .into();
-
         let mut materials = world.resource_mut::<Assets<LightMaterial>>();
 
         LightRenderData {
-            mesh: mesh_handle,
             material_map: enum_map! {
                 LightColor::Green => materials.add(LightMaterial::from(LightColor::Green)).into(),
                 LightColor::Purple => materials.add(LightMaterial::from(LightColor::Purple)).into(),
@@ -41,8 +41,73 @@ impl FromWorld for LightRenderData {
     }
 }
 
-/// Custom [`Material2d`] that will use our custom WGSL shader to draw
-/// [`LightSegment`](super::segments::LightSegmentBundle)s.
+/// Builds the mesh of a whole light beam going through `pts`, as a strip of
+/// [`LIGHT_SEGMENT_THICKNESS`] with mitred joints at each bounce so that segments don't overlap.
+/// The v coordinate goes across the beam, which is what the light shader uses. Returns [`None`]
+/// if the beam is too short to be seen.
+pub fn light_beam_mesh(pts: &[Vec2]) -> Option<Mesh> {
+    let half_thickness = LIGHT_SEGMENT_THICKNESS / 2.0;
+
+    // zero length segments have no direction to offset the vertices along
+    let mut path: Vec<Vec2> = Vec::with_capacity(pts.len());
+    for &pt in pts {
+        if path.last().is_none_or(|last| last.distance(pt) > 0.1) {
+            path.push(pt);
+        }
+    }
+    if path.len() < 2 {
+        return None;
+    }
+
+    let normals: Vec<Vec2> = path
+        .windows(2)
+        .map(|w| (w[1] - w[0]).normalize().perp())
+        .collect();
+
+    let mut positions = Vec::with_capacity(path.len() * 2);
+    let mut uvs = Vec::with_capacity(path.len() * 2);
+    let mut distance = 0.0;
+    for (i, &pt) in path.iter().enumerate() {
+        if i > 0 {
+            distance += path[i - 1].distance(pt);
+        }
+        let offset = match (i.checked_sub(1).map(|prev| normals[prev]), normals.get(i)) {
+            (Some(prev), Some(&next)) => {
+                // beams that bounce straight back have no miter
+                let miter = (prev + next).normalize_or(next);
+                miter * half_thickness / miter.dot(next).max(1.0 / LIGHT_MITER_LIMIT)
+            }
+            (Some(normal), None) | (None, Some(&normal)) => normal * half_thickness,
+            (None, None) => unreachable!(),
+        };
+        positions.push((pt + offset).extend(0.0).to_array());
+        positions.push((pt - offset).extend(0.0).to_array());
+        uvs.push([distance, 0.0]);
+        uvs.push([distance, 1.0]);
+    }
+
+    let mut indices = Vec::with_capacity(normals.len() * 6);
+    for i in 0..normals.len() as u32 {
+        let (left, right) = (i * 2, i * 2 + 1);
+        indices.extend([right, right + 2, left + 2, right, left + 2, left]);
+    }
+
+    Some(
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            vec![[0.0, 0.0, 1.0]; positions.len()],
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices)),
+    )
+}
+
+/// Custom [`Material2d`] that will use our custom WGSL shader to draw light beams.
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
 pub struct LightMaterial {
     #[uniform(0)]
@@ -66,10 +131,40 @@ impl Material2d for LightMaterial {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bevy::render::mesh::VertexAttributeValues;
     use std::mem;
 
     #[test]
     fn light_material_alignment() {
         assert_eq!(mem::size_of::<LightMaterial>() % 16, 0);
     }
+
+    fn positions(mesh: &Mesh) -> Vec<Vec2> {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("Light beam mesh should have positions");
+        };
+        positions.iter().map(|p| Vec2::new(p[0], p[1])).collect()
+    }
+
+    #[test]
+    fn light_beam_mesh_mitres_bounces() {
+        let half = LIGHT_SEGMENT_THICKNESS / 2.0;
+        let mesh =
+            light_beam_mesh(&[Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)]).unwrap();
+        let positions = positions(&mesh);
+
+        assert_eq!(positions.len(), 6);
+        assert_eq!(mesh.indices().unwrap().len(), 12);
+        assert!(positions[0].distance(Vec2::new(0.0, half)) < 1e-4);
+        // the corner vertices sit on the diagonal so both segments meet without overlapping
+        assert!(positions[2].distance(Vec2::new(10.0 - half, half)) < 1e-4);
+        assert!(positions[3].distance(Vec2::new(10.0 + half, -half)) < 1e-4);
+    }
+
+    #[test]
+    fn light_beam_mesh_skips_empty_beams() {
+        assert!(light_beam_mesh(&[Vec2::ONE, Vec2::ONE]).is_none());
+    }
 }
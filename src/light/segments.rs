@@ -1,14 +1,25 @@
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{
+    diagnostic::{DiagnosticPath, Diagnostics},
+    ecs::system::SystemParam,
+    math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume},
+    prelude::*,
+    utils::{HashMap, HashSet, Instant},
+};
 use bevy_rapier2d::prelude::*;
+use enum_map::EnumMap;
 
 use super::{
-    render::{LightMaterial, LightRenderData},
+    bridge::light_bridge_bundle,
+    render::{light_beam_mesh, LightRenderData},
     BlackRayComponent, LightBeamSource, LightColor, LightSegmentZMarker, LIGHT_SPEED,
 };
 use crate::{
     camera::HIGHRES_LAYER,
-    level::{mirror::Mirror, sensor::LightSensor},
-    lighting::LineLight2d,
+    level::{
+        sensor::LightSensor,
+        surface::{Surface, SurfaceInfo, SurfaceMaterial, SurfaceMaterials, SurfaceTable},
+    },
+    lighting::{LineLight2d, LineLight2dBuffer},
     particle::spark::SparkExplosionEvent,
     shared::GroupLabel,
 };
@@ -20,43 +31,86 @@ pub struct LightSegment {
 }
 
 /// [`Bundle`] used in the initialization of the [`LightSegmentCache`] to spawn segment entities.
+/// Segments aren't drawn themselves (the whole beam is one mesh and its lights are one
+/// [`LineLight2dBuffer`]), they hold the colliders and bridge of one straight part of the beam.
 #[derive(Bundle, Debug, Clone, Default)]
 pub struct LightSegmentBundle {
     pub segment: LightSegment,
-    pub mesh: Mesh2d,
-    pub material: MeshMaterial2d<LightMaterial>,
     pub visibility: Visibility,
     pub transform: Transform,
 }
 
+/// Marker [`Component`] for the entity holding the mesh of a whole light beam.
+#[derive(Default, Component)]
+pub struct LightBeamMesh;
+
+/// Entities used to display a single light beam.
+struct LightBeamEntities {
+    mesh: Entity,
+    segments: Vec<Entity>,
+}
+
 /// [`Resource`] used to store [`Entity`] handles to the light segments so they aren't added and
 /// despawned every frame. See [`simulate_light_sources`] for details.
 #[derive(Resource, Default)]
 pub struct LightSegmentCache {
-    segments: HashMap<Entity, (Vec<Entity>, LightColor)>,
+    segments: HashMap<Entity, LightBeamEntities>,
+    /// Hidden segments of despawned beams, reused by later beams of the same color so their
+    /// colliders and bridges aren't spawned again
+    free_segments: EnumMap<LightColor, Vec<Entity>>,
+}
+
+impl LightSegmentCache {
+    /// Despawns the mesh of the beam of `source_entity` and removes its [`Mesh`] asset. Its
+    /// segments are hidden and kept for the next beam of the same color.
+    fn release(&mut self, commands: &mut Commands, source_entity: Entity, color: LightColor) {
+        let Some(beam) = self.segments.remove(&source_entity) else {
+            return;
+        };
+        let mesh_entity = beam.mesh;
+        commands.queue(move |world: &mut World| {
+            let mesh = world
+                .get::<Mesh2d>(mesh_entity)
+                .map(|mesh_2d| mesh_2d.0.clone());
+            if let Some(mesh) = mesh.filter(Handle::is_strong) {
+                world.resource_mut::<Assets<Mesh>>().remove(&mesh);
+            }
+            world.despawn(mesh_entity);
+        });
+        for &segment in beam.segments.iter() {
+            // required for white beam
+            commands
+                .entity(segment)
+                .insert((Transform::default(), Visibility::Hidden));
+        }
+        self.free_segments[color].extend(beam.segments);
+    }
 }
 
-/// Local variable for [`simulate_light_sources`] used to store the handle to the audio SFX
+/// Local variable for [`simulate_light_sources`] used to store the handles to the bounce SFX of
+/// every [`SurfaceInfo`] in the [`SurfaceTable`], keyed by their sound path.
 pub struct LightBounceSfx {
-    bounce: [Handle<AudioSource>; 3],
-    reflect: [Handle<AudioSource>; 3],
+    sounds: HashMap<String, [Handle<AudioSource>; 3]>,
 }
 
 impl FromWorld for LightBounceSfx {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
-        LightBounceSfx {
-            bounce: [
-                asset_server.load("sfx/light/light-bounce-1.wav"),
-                asset_server.load("sfx/light/light-bounce-2.wav"),
-                asset_server.load("sfx/light/light-bounce-3.wav"),
-            ],
-            reflect: [
-                asset_server.load("sfx/light/light-bounce-1-reflect.wav"),
-                asset_server.load("sfx/light/light-bounce-2-reflect.wav"),
-                asset_server.load("sfx/light/light-bounce-3-reflect.wav"),
-            ],
+        let mut sounds = HashMap::new();
+        for info in world.resource::<SurfaceTable>().iter() {
+            sounds.entry(info.sound.clone()).or_insert_with(|| {
+                [1, 2, 3].map(|bounce| asset_server.load(info.sound_path(bounce)))
+            });
         }
+        LightBounceSfx { sounds }
+    }
+}
+
+impl LightBounceSfx {
+    /// The sound played when a beam hits `surface` on its `bounce`th bounce, starting from 0.
+    fn get(&self, surface: &SurfaceInfo, bounce: usize) -> Handle<AudioSource> {
+        let sounds = &self.sounds[&surface.sound];
+        sounds.get(bounce).unwrap_or(&sounds[2]).clone()
     }
 }
 
@@ -65,6 +119,8 @@ pub struct LightBeamIntersection {
     pub entity: Entity,
     pub point: Vec2,
     pub time: f32,
+    /// Material of the surface that was hit
+    pub material: SurfaceMaterial,
 }
 
 /// Stores information about the trajectory of a LightBeam
@@ -93,6 +149,115 @@ impl LightBeamPlayback {
 #[derive(Default, Debug, Component)]
 pub struct PrevLightBeamPlayback {
     pub intersections: Vec<Option<LightBeamIntersection>>,
+    /// Set to how far the beam had traveled once it stopped at its last intersection. Until a
+    /// collider changes or the beam is pulled back before this point, its path can't change, so
+    /// the rays aren't cast again.
+    stable_at: Option<f32>,
+}
+
+/// Time spent in [`simulate_light_sources`], in milliseconds.
+pub const LIGHT_SIMULATION_TIME: DiagnosticPath =
+    DiagnosticPath::const_new("light/simulation_time");
+
+/// Number of light beams whose path was traced again in [`simulate_light_sources`].
+pub const TRACED_LIGHT_BEAMS: DiagnosticPath = DiagnosticPath::const_new("light/traced_beams");
+
+/// [`SystemParam`] used to find the colliders that light beams can hit that have moved, changed,
+/// or been added or removed since the last [`FixedUpdate`].
+#[allow(clippy::type_complexity)]
+#[derive(SystemParam)]
+pub struct LightColliderChanges<'w, 's> {
+    q_changed: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Collider,
+            &'static GlobalTransform,
+            Option<&'static CollisionGroups>,
+        ),
+        Or<(
+            Changed<GlobalTransform>,
+            Changed<Collider>,
+            Changed<CollisionGroups>,
+        )>,
+    >,
+    removed: RemovedComponents<'w, 's, Collider>,
+}
+
+impl LightColliderChanges<'_, '_> {
+    pub fn collect(&mut self) -> ChangedLightColliders {
+        let hittable = GroupLabel::TERRAIN
+            | GroupLabel::PLATFORM
+            | GroupLabel::LIGHT_SENSOR
+            | GroupLabel::WHITE_RAY
+            | GroupLabel::BLACK_RAY;
+        let rays = GroupLabel::LIGHT_RAY
+            | GroupLabel::WHITE_RAY
+            | GroupLabel::BLUE_RAY
+            | GroupLabel::BLACK_RAY;
+
+        let changed = self
+            .q_changed
+            .iter()
+            .filter(|(_, _, _, groups)| {
+                groups.is_none_or(|groups| {
+                    groups.memberships.intersects(hittable) && groups.filters.intersects(rays)
+                })
+            })
+            .map(|(entity, collider, transform, _)| (entity, collider_aabb(collider, transform)))
+            .collect();
+        ChangedLightColliders {
+            changed,
+            removed: self.removed.read().collect(),
+        }
+    }
+}
+
+/// The world space bounds of `collider`.
+fn collider_aabb(collider: &Collider, transform: &GlobalTransform) -> Aabb2d {
+    let local = collider.raw.compute_local_aabb();
+    let corners = [
+        Vec2::new(local.mins.x, local.mins.y),
+        Vec2::new(local.maxs.x, local.mins.y),
+        Vec2::new(local.mins.x, local.maxs.y),
+        Vec2::new(local.maxs.x, local.maxs.y),
+    ];
+    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+    let isometry = Isometry2d::new(
+        translation.xy(),
+        Rot2::radians(rotation.to_euler(EulerRot::XYZ).2),
+    );
+    Aabb2d::from_point_cloud(isometry, &corners)
+}
+
+/// The colliders found by [`LightColliderChanges`].
+pub struct ChangedLightColliders {
+    changed: Vec<(Entity, Aabb2d)>,
+    removed: Vec<Entity>,
+}
+
+impl ChangedLightColliders {
+    /// Whether any of the changes can move the path of a beam: a collider it hits changed or went
+    /// away, or a changed collider is somewhere near the points of its path.
+    fn affect(&self, intersections: &[Option<LightBeamIntersection>], path: &[Vec2]) -> bool {
+        let hits = |entity: Entity| {
+            intersections
+                .iter()
+                .flatten()
+                .any(|intersection| intersection.entity == entity)
+        };
+        if self.removed.iter().any(|entity| hits(*entity)) {
+            return true;
+        }
+        if self.changed.is_empty() {
+            return false;
+        }
+        let bounds = Aabb2d::from_point_cloud(Isometry2d::IDENTITY, path).grow(Vec2::splat(1.0));
+        self.changed
+            .iter()
+            .any(|(entity, aabb)| hits(*entity) || aabb.intersects(&bounds))
+    }
 }
 
 const LIGHT_MAX_SEGMENTS: usize = 15;
@@ -101,7 +266,7 @@ pub fn play_light_beam(
     rapier_context: &mut RapierContext,
     source: &LightBeamSource,
     black_ray_qry: &Query<(Entity, &BlackRayComponent)>,
-    q_mirrors: &Query<&Mirror>,
+    surfaces: &SurfaceMaterials,
 ) -> LightBeamPlayback {
     let mut ray_pos = source.start_pos;
     let mut ray_dir = source.start_dir;
@@ -155,7 +320,8 @@ pub fn play_light_beam(
             playback.end_point = Some(final_point);
             break;
         };
-        if q_mirrors.contains(entity) {
+        let material = surfaces.get(entity);
+        if material == SurfaceMaterial::Mirror {
             extra_bounces_from_mirror += 1;
         }
 
@@ -172,6 +338,7 @@ pub fn play_light_beam(
             entity,
             point: intersection.point,
             time: playback.elapsed_time,
+            material,
         });
 
         ray_pos = intersection.point;
@@ -180,7 +347,12 @@ pub fn play_light_beam(
             ray_qry = ray_qry.exclude_collider(entity);
         }
 
-        if black_ray_qry.get(entity).is_ok() {
+        if black_ray_qry.get(entity).is_ok()
+            || matches!(
+                material,
+                SurfaceMaterial::Absorbing | SurfaceMaterial::Diffuse
+            )
+        {
             break;
         }
         i += 1;
@@ -192,24 +364,45 @@ pub fn play_light_beam(
 #[derive(Default, Component)]
 pub struct LightBeamPoints(Vec<Vec2>);
 
+impl LightBeamPoints {
+    /// The length of the light beam, which is also how far it has traveled.
+    pub fn length(&self) -> f32 {
+        self.0.windows(2).map(|w| w[0].distance(w[1])).sum()
+    }
+}
+
+/// Marker [`Component`] for light beams that are being retracted back into their source. These are
+/// not advanced by [`tick_light_sources`].
+#[derive(Default, Component)]
+pub struct RecallingBeam;
+
 /// [`System`] that runs on [`Update`], calculating the [`Transform`] of light segments from the
 /// corresponding [`LightBeamSource`]. Note that this calculation happens every frame, so instead of
 /// rapidly spawning/despawning the entities, we spawn them and cache them in the
 /// [`LightSegmentCache`], then modify their [`Visibility`] and [`Transform`]s.
 ///
-/// If needed, optimization work can be done by recalculating only segments that are currently
-/// changing (segments already "stabilized" usually won't move).
+/// Beams that have stopped at their last intersection are only traced again when a collider on
+/// or near their path changes (see [`ChangedLightColliders::affect`]). Rapier only sees collider
+/// changes on the next physics step, so a beam has to go through one tick without such changes
+/// before it counts as stable.
 #[allow(clippy::too_many_arguments)]
 pub fn simulate_light_sources(
     mut commands: Commands,
-    mut q_light_sources: Query<(Entity, &mut LightBeamSource, &mut PrevLightBeamPlayback)>,
+    mut q_light_sources: Query<(
+        Entity,
+        &mut LightBeamSource,
+        &mut PrevLightBeamPlayback,
+        Option<&LightBeamPoints>,
+    )>,
     q_black_ray: Query<(Entity, &BlackRayComponent)>,
     mut q_rapier: Query<&mut RapierContext>,
     mut q_light_sensor: Query<&mut LightSensor>,
     // used to tell if a collision was against a white beam (a different sound is played)
     q_segments: Query<&LightSegment, Without<LightSegmentZMarker>>,
     light_bounce_sfx: Local<LightBounceSfx>,
-    q_mirrors: Query<&Mirror>,
+    surfaces: SurfaceMaterials,
+    mut collider_changes: LightColliderChanges,
+    mut diagnostics: Diagnostics,
     mut ev_spark_explosion: EventWriter<SparkExplosionEvent>,
 ) {
     let Ok(rapier_context) = q_rapier.get_single_mut() else {
@@ -218,9 +411,29 @@ pub fn simulate_light_sources(
     // Reborrow!!!
     let rapier_context = rapier_context.into_inner();
 
-    for (source_entity, mut source, mut prev_playback) in q_light_sources.iter_mut() {
-        let playback = play_light_beam(rapier_context, &source, &q_black_ray, &q_mirrors);
+    let start = Instant::now();
+    let changes = collider_changes.collect();
+    let mut traced = 0;
+
+    for (source_entity, mut source, mut prev_playback, prev_points) in q_light_sources.iter_mut() {
+        let colliders_changed = changes.affect(
+            &prev_playback.intersections,
+            prev_points.map_or(&[source.start_pos], |points| &points.0),
+        );
+        if colliders_changed {
+            prev_playback.stable_at = None;
+        }
+        if prev_playback
+            .stable_at
+            .is_some_and(|stable_at| source.time_traveled >= stable_at)
+        {
+            continue;
+        }
+        traced += 1;
+
+        let playback = play_light_beam(rapier_context, &source, &q_black_ray, &surfaces);
         let mut pts: Vec<Vec2> = playback.iter_points(&source).collect();
+        let mut path_changed = false;
 
         let intersections = playback.intersections.len();
         for i in 0..intersections {
@@ -231,6 +444,7 @@ pub fn simulate_light_sources(
 
             // diff intersection
             if !is_same_intersection {
+                path_changed = true;
                 let is_closer = prev_x.is_none_or(|prev_x| prev_x.time > new_x.time);
 
                 // remvoe all points after the current intersection
@@ -263,7 +477,11 @@ pub fn simulate_light_sources(
                 if add_intersection {
                     pts[i + 1] = new_x.point;
                     if let Ok(mut sensor) = q_light_sensor.get_mut(new_x.entity) {
-                        sensor.hit_by[source.color] = true;
+                        // directional sensors ignore beams coming from outside their cone
+                        let beam_dir = (new_x.point - pts[i]).normalize_or_zero();
+                        if sensor.accepts(beam_dir) {
+                            sensor.hit_by[source.color] = true;
+                        }
                     }
                     if i >= prev_playback.intersections.len() {
                         assert!(i == prev_playback.intersections.len());
@@ -275,29 +493,21 @@ pub fn simulate_light_sources(
                 }
 
                 if play_sound && source.color != LightColor::Black {
-                    let reflect = match q_segments.get(new_x.entity) {
-                        Ok(segment) => segment.color == LightColor::White,
-                        _ => false,
+                    let surface = match q_segments.get(new_x.entity) {
+                        Ok(segment) if segment.color == LightColor::White => {
+                            surfaces.table().get(Surface::WhiteBeam)
+                        }
+                        _ => surfaces.info(new_x.entity),
                     };
-                    let audio = if reflect {
-                        light_bounce_sfx
-                            .reflect
-                            .get(i)
-                            .unwrap_or(&light_bounce_sfx.reflect[2])
-                    } else {
-                        light_bounce_sfx
-                            .bounce
-                            .get(i)
-                            .unwrap_or(&light_bounce_sfx.bounce[2])
-                    }
-                    .clone();
+                    let audio = light_bounce_sfx.get(surface, i);
+                    let playback_settings = PlaybackSettings::DESPAWN.with_speed(surface.speed);
                     ev_spark_explosion.send(SparkExplosionEvent {
                         pos: new_x.point,
                         color: source.color.light_beam_color(),
                     });
                     commands
                         .entity(new_x.entity)
-                        .with_child((AudioPlayer::new(audio), PlaybackSettings::DESPAWN));
+                        .with_child((AudioPlayer::new(audio), playback_settings));
                 }
 
                 prev_playback.intersections.truncate(i + 1);
@@ -308,8 +518,26 @@ pub fn simulate_light_sources(
                 prev_playback.intersections[i] = Some(new_x);
             }
         }
+        // the beam got shorter (for example while being recalled), so un-hit everything past its
+        // new end
+        if prev_playback.intersections.len() > intersections {
+            path_changed = true;
+            for intersection in prev_playback.intersections.drain(intersections..).flatten() {
+                if let Ok(mut sensor) = q_light_sensor.get_mut(intersection.entity) {
+                    sensor.hit_by[source.color] = false;
+                }
+            }
+        }
+        prev_playback.stable_at =
+            (!colliders_changed && !path_changed && playback.end_point.is_none())
+                .then_some(playback.elapsed_time);
         commands.entity(source_entity).insert(LightBeamPoints(pts));
     }
+
+    diagnostics.add_measurement(&LIGHT_SIMULATION_TIME, || {
+        start.elapsed().as_secs_f64() * 1000.0
+    });
+    diagnostics.add_measurement(&TRACED_LIGHT_BEAMS, || traced as f64);
 }
 
 pub fn spawn_needed_segments(
@@ -323,96 +551,144 @@ pub fn spawn_needed_segments(
         // lazily spawn segment entities until there are enough segments to display the light beam
         // path
         if !segment_cache.segments.contains_key(&entity) {
-            segment_cache
-                .segments
-                .insert(entity, (vec![], source.color));
-        }
-
-        while segment_cache.segments[&entity].0.len() < segments.min(LIGHT_MAX_SEGMENTS) {
-            let id = commands
+            let mesh = commands
                 .spawn((
-                    LightSegmentBundle {
-                        segment: LightSegment {
-                            color: source.color,
-                        },
-                        mesh: light_render_data.mesh.clone(),
-                        material: light_render_data.material_map[source.color].clone(),
-                        visibility: Visibility::Hidden,
-                        transform: Transform::default(),
-                    },
+                    LightBeamMesh,
+                    LineLight2dBuffer::default(),
+                    Mesh2d::default(),
+                    light_render_data.material_map[source.color].clone(),
+                    Transform::default(),
+                    Visibility::Hidden,
                     HIGHRES_LAYER,
                 ))
-                .with_child(LineLight2d {
-                    color: source.color.lighting_color().extend(1.0),
-                    half_length: 10.0,
-                    radius: 20.0,
-                    volumetric_intensity: 0.04,
-                })
                 .id();
-            // White beams need colliders
-            if source.color == LightColor::White {
-                commands.entity(id).insert((
-                    Collider::cuboid(0.5, 0.5),
-                    Sensor,
-                    CollisionGroups::new(
-                        GroupLabel::WHITE_RAY,
-                        GroupLabel::TERRAIN
-                            | GroupLabel::PLATFORM
-                            | GroupLabel::LIGHT_SENSOR
-                            | GroupLabel::LIGHT_RAY
-                            | GroupLabel::BLUE_RAY
-                            | GroupLabel::BLACK_RAY,
-                    ),
-                ));
-            }
-            // Black beams need Black_Ray_Component and colliders
-            if source.color == LightColor::Black {
-                commands.entity(id).insert((
-                    BlackRayComponent,
-                    Sensor,
-                    Collider::cuboid(0.5, 0.5),
-                    CollisionGroups::new(
-                        GroupLabel::BLACK_RAY,
-                        GroupLabel::TERRAIN
-                            | GroupLabel::PLATFORM
-                            | GroupLabel::LIGHT_SENSOR
-                            | GroupLabel::LIGHT_RAY
-                            | GroupLabel::BLUE_RAY
-                            | GroupLabel::WHITE_RAY,
-                    ),
-                ));
-            }
-            segment_cache.segments.get_mut(&entity).unwrap().0.push(id);
-            //segment_cache.segments[&source.color].push(id);
+            segment_cache.segments.insert(
+                entity,
+                LightBeamEntities {
+                    mesh,
+                    segments: vec![],
+                },
+            );
+        }
+
+        while segment_cache.segments[&entity].segments.len() < segments.min(LIGHT_MAX_SEGMENTS) {
+            let id = match segment_cache.free_segments[source.color].pop() {
+                Some(id) => id,
+                None => spawn_segment(&mut commands, source.color),
+            };
+            segment_cache
+                .segments
+                .get_mut(&entity)
+                .unwrap()
+                .segments
+                .push(id);
         }
     }
 }
 
+/// Spawns a hidden segment for a beam of the given color, with the colliders white and black
+/// beams need.
+fn spawn_segment(commands: &mut Commands, color: LightColor) -> Entity {
+    let id = commands
+        .spawn(LightSegmentBundle {
+            segment: LightSegment { color },
+            visibility: Visibility::Hidden,
+            transform: Transform::default(),
+        })
+        .with_child(light_bridge_bundle())
+        .id();
+    // White beams need colliders
+    if color == LightColor::White {
+        commands.entity(id).insert((
+            Collider::cuboid(0.5, 0.5),
+            Sensor,
+            CollisionGroups::new(
+                GroupLabel::WHITE_RAY,
+                GroupLabel::TERRAIN
+                    | GroupLabel::PLATFORM
+                    | GroupLabel::LIGHT_SENSOR
+                    | GroupLabel::LIGHT_RAY
+                    | GroupLabel::BLUE_RAY
+                    | GroupLabel::BLACK_RAY,
+            ),
+        ));
+    }
+    // Black beams need Black_Ray_Component and colliders
+    if color == LightColor::Black {
+        commands.entity(id).insert((
+            BlackRayComponent,
+            Sensor,
+            Collider::cuboid(0.5, 0.5),
+            CollisionGroups::new(
+                GroupLabel::BLACK_RAY,
+                GroupLabel::TERRAIN
+                    | GroupLabel::PLATFORM
+                    | GroupLabel::LIGHT_SENSOR
+                    | GroupLabel::LIGHT_RAY
+                    | GroupLabel::BLUE_RAY
+                    | GroupLabel::WHITE_RAY,
+            ),
+        ));
+    }
+    id
+}
+
+/// [`System`] that rebuilds the mesh and lights of each light beam whose path changed, and moves
+/// its segments along the path.
+#[allow(clippy::type_complexity)]
 pub fn visually_sync_segments(
-    q_light_sources: Query<(Entity, &LightBeamSource, &LightBeamPoints)>,
+    q_light_sources: Query<(Entity, &LightBeamSource, Ref<LightBeamPoints>)>,
     segment_cache: Res<LightSegmentCache>,
-    mut q_segments: Query<(&Children, &mut Transform, &mut Visibility), With<LightSegment>>,
-    mut q_line_lights: Query<&mut LineLight2d>,
+    mut q_segments: Query<
+        (&mut Transform, &mut Visibility),
+        (With<LightSegment>, Without<LightBeamMesh>),
+    >,
+    mut q_beam_meshes: Query<
+        (
+            &mut Mesh2d,
+            &mut LineLight2dBuffer,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<LightBeamMesh>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
     q_light_segment_z: Query<&GlobalTransform, With<LightSegmentZMarker>>,
 ) {
     let Ok(light_segment_z) = q_light_segment_z.get_single() else {
         return;
     };
-    for (entity, _source, pts) in q_light_sources.iter() {
+    for (entity, source, pts) in q_light_sources.iter() {
+        let beam = &segment_cache.segments[&entity];
+
+        let Ok((mut mesh_2d, mut lights, mut transform, mut visibility)) =
+            q_beam_meshes.get_mut(beam.mesh)
+        else {
+            panic!("Light beam mesh doesn't have mesh, lights, transform or visibility!");
+        };
+        // hidden meshes are rebuilt as well, because they are hidden when the level changes
+        if pts.is_changed() || *visibility == Visibility::Hidden {
+            transform.translation.z = light_segment_z.translation().z;
+            lights.lights = segment_lights(&pts.0, source.color).collect();
+            match light_beam_mesh(&pts.0) {
+                Some(mesh) => {
+                    match meshes.get_mut(&mesh_2d.0) {
+                        Some(old_mesh) => *old_mesh = mesh,
+                        None => mesh_2d.0 = meshes.add(mesh),
+                    }
+                    *visibility = Visibility::Visible;
+                }
+                None => *visibility = Visibility::Hidden,
+            }
+        }
+
         let pts = &pts.0;
         // use the light beam path to set the transform of the segments currently in the cache
 
-        for (i, segment) in segment_cache.segments[&entity].0.iter().enumerate() {
-            let Ok((children, mut c_transform, mut c_visibility)) = q_segments.get_mut(*segment)
-            else {
+        for (i, segment) in beam.segments.iter().enumerate() {
+            let Ok((mut c_transform, mut c_visibility)) = q_segments.get_mut(*segment) else {
                 panic!("Segment doesn't have transform or visibility!");
             };
-            let Some(line_light_entity) = children.first() else {
-                panic!("Segment doesn't have line light!");
-            };
-            let Ok(mut line_light) = q_line_lights.get_mut(*line_light_entity) else {
-                panic!("Segment doesn't have line light!");
-            };
 
             if i + 1 < pts.len() && pts[i].distance(pts[i + 1]) > 0.1 {
                 let midpoint = pts[i]
@@ -425,45 +701,149 @@ pub fn visually_sync_segments(
                     .with_scale(scale)
                     .with_rotation(Quat::from_rotation_z(rotation));
 
-                line_light.half_length = scale.x / 2.0;
-                *c_transform = transform;
-                *c_visibility = Visibility::Visible;
+                // only write changes so that stable white beams don't look like moved colliders
+                c_transform.set_if_neq(transform);
+                c_visibility.set_if_neq(Visibility::Visible);
             } else {
                 // required for white beam
-                line_light.half_length = 0.0;
-                *c_transform = Transform::default();
-                *c_visibility = Visibility::Hidden;
+                c_transform.set_if_neq(Transform::default());
+                c_visibility.set_if_neq(Visibility::Hidden);
             }
         }
     }
 }
 
+/// The [`LineLight2d`] along each straight part of the path `pts`, placed relative to the beam's
+/// mesh.
+fn segment_lights(
+    pts: &[Vec2],
+    color: LightColor,
+) -> impl Iterator<Item = (Transform, LineLight2d)> + '_ {
+    pts.windows(2)
+        .take(LIGHT_MAX_SEGMENTS)
+        .filter(|w| w[0].distance(w[1]) > 0.1)
+        .map(move |w| {
+            let length = w[0].distance(w[1]);
+            let transform = Transform::from_translation(w[0].midpoint(w[1]).extend(0.0))
+                .with_scale(Vec3::new(length, 1., 1.))
+                .with_rotation(Quat::from_rotation_z((w[1] - w[0]).to_angle()));
+            let light = LineLight2d {
+                color: color.lighting_color().extend(1.0),
+                half_length: length / 2.0,
+                radius: 20.0,
+                volumetric_intensity: 0.04,
+            };
+            (transform, light)
+        })
+}
+
+/// Removes a light beam from the world, un-hitting every [`LightSensor`] it was hitting and
+/// despawning its segments.
+pub fn despawn_light_beam(
+    commands: &mut Commands,
+    source_entity: Entity,
+    source: &LightBeamSource,
+    prev_playback: &PrevLightBeamPlayback,
+    q_light_sensor: &mut Query<&mut LightSensor>,
+    segment_cache: &mut LightSegmentCache,
+) {
+    for intersection in prev_playback.intersections.iter().flatten() {
+        if let Ok(mut sensor) = q_light_sensor.get_mut(intersection.entity) {
+            sensor.hit_by[source.color] = false;
+        }
+    }
+    segment_cache.release(commands, source_entity, source.color);
+    commands.entity(source_entity).despawn_recursive();
+}
+
+/// [`Component`] for the point light a light beam gets when it ends on a
+/// [`SurfaceMaterial::Diffuse`] surface.
+#[derive(Component)]
+pub struct BeamGlow {
+    source: Entity,
+}
+
+/// Returns where the beam ends if it ends on a [`SurfaceMaterial::Diffuse`] surface.
+fn diffuse_end(prev_playback: &PrevLightBeamPlayback) -> Option<Vec2> {
+    prev_playback
+        .intersections
+        .last()
+        .copied()
+        .flatten()
+        .filter(|intersection| intersection.material == SurfaceMaterial::Diffuse)
+        .map(|intersection| intersection.point)
+}
+
+/// [`System`] that makes light beams glow where they end on a [`SurfaceMaterial::Diffuse`]
+/// surface. Glows whose beam is gone or no longer ends on such a surface are despawned.
+pub fn sync_beam_glows(
+    mut commands: Commands,
+    q_light_sources: Query<(Entity, &LightBeamSource, &PrevLightBeamPlayback)>,
+    mut q_glows: Query<(Entity, &BeamGlow, &mut Transform)>,
+) {
+    let mut glowing = HashSet::new();
+    for (entity, glow, mut transform) in q_glows.iter_mut() {
+        let end = q_light_sources
+            .get(glow.source)
+            .ok()
+            .and_then(|(_, _, prev_playback)| diffuse_end(prev_playback));
+        match end {
+            Some(end) => {
+                transform.translation = end.extend(transform.translation.z);
+                glowing.insert(glow.source);
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    for (entity, source, prev_playback) in q_light_sources.iter() {
+        if glowing.contains(&entity) {
+            continue;
+        }
+        if let Some(end) = diffuse_end(prev_playback) {
+            commands.spawn((
+                BeamGlow { source: entity },
+                LineLight2d::point(source.color.lighting_color().extend(1.0), 20.0, 0.03),
+                Transform::from_translation(end.extend(0.0)),
+                Visibility::Visible,
+            ));
+        }
+    }
+}
+
 /// [`System`] that runs on [`FixedUpdate`], advancing the distance the light beam can travel.
-pub fn tick_light_sources(mut q_light_sources: Query<&mut LightBeamSource>) {
+pub fn tick_light_sources(
+    mut q_light_sources: Query<&mut LightBeamSource, Without<RecallingBeam>>,
+) {
     for mut source in q_light_sources.iter_mut() {
         source.time_traveled += LIGHT_SPEED;
     }
 }
 
 /// [`System`] that is responsible for hiding all of the [`LightSegment`](LightSegmentBundle)s
-/// and despawning [`LightBeamSource`]s when the level changes.
+/// and despawning [`LightBeamSource`]s (along with their meshes) when the level changes.
 pub fn cleanup_light_sources(
     mut commands: Commands,
     q_light_sources: Query<(Entity, &LightBeamSource)>,
-    segment_cache: Res<LightSegmentCache>,
+    mut segment_cache: ResMut<LightSegmentCache>,
     mut q_segments: Query<(&mut Transform, &mut Visibility), With<LightSegment>>,
+    mut q_beam_meshes: Query<&mut Visibility, (With<LightBeamMesh>, Without<LightSegment>)>,
 ) {
     // FIXME: should make these entities children of the level so that they are despawned
     // automagically (?)
 
     for (entity, light_beam_source) in q_light_sources.iter() {
         if light_beam_source.color != LightColor::Black {
+            segment_cache.release(&mut commands, entity, light_beam_source.color);
             commands.entity(entity).despawn_recursive();
         }
     }
 
-    segment_cache.segments.iter().for_each(|(_, items)| {
-        for &entity in items.0.iter() {
+    segment_cache.segments.iter().for_each(|(_, beam)| {
+        if let Ok(mut visibility) = q_beam_meshes.get_mut(beam.mesh) {
+            *visibility = Visibility::Hidden;
+        }
+        for &entity in beam.segments.iter() {
             let (mut transform, mut visibility) = q_segments
                 .get_mut(entity)
                 .expect("Segment should have visibility");
@@ -18,14 +18,15 @@ use bevy::{
         render_phase::{PhaseItem, RenderCommand, RenderCommandResult, TrackedRenderPass},
         render_resource::{binding_types::uniform_buffer, *},
         renderer::{RenderDevice, RenderQueue},
+        sync_world::TemporaryRenderEntity,
         view::{check_visibility, ViewTarget, VisibilitySystems},
-        Render, RenderApp, RenderSet,
+        Extract, ExtractSchedule, Render, RenderApp, RenderSet,
     },
     sprite::Mesh2dPipeline,
 };
 use bytemuck::{Pod, Zeroable};
 
-use super::render::PostProcessRes;
+use super::{occluder::Occluder2dGroups, render::PostProcessRes};
 
 pub struct LineLight2dPlugin;
 
@@ -45,10 +46,12 @@ impl Plugin for LineLight2dPlugin {
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
-        render_app.add_systems(
-            Render,
-            prepare_line_light_2d_bind_group.in_set(RenderSet::PrepareBindGroups),
-        );
+        render_app
+            .add_systems(ExtractSchedule, extract_line_light_2d_buffers)
+            .add_systems(
+                Render,
+                prepare_line_light_2d_bind_group.in_set(RenderSet::PrepareBindGroups),
+            );
     }
     fn finish(&self, app: &mut App) {
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
@@ -80,6 +83,20 @@ impl LineLight2d {
     }
 }
 
+/// Many [`LineLight2d`]s owned by one entity, each placed by its own [`Transform`] relative to the
+/// entity. Used for lights that change together, like the straight parts of a light beam, so
+/// they don't need an entity each. Buffered lights aren't culled against the view.
+#[derive(Component, Default, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct LineLight2dBuffer {
+    pub lights: Vec<(Transform, LineLight2d)>,
+}
+
+/// [`Component`] for render world lights extracted from a [`LineLight2dBuffer`], holding the main
+/// world entity that owns the buffer.
+#[derive(Component, Clone, Copy)]
+pub struct BufferedLineLight2d(pub Entity);
+
 pub fn calculate_line_light_2d_bounds(
     mut commands: Commands,
     q_light_changed: Query<(Entity, &LineLight2d), Changed<LineLight2d>>,
@@ -103,29 +120,69 @@ impl ExtractComponent for LineLight2d {
     fn extract_component(
         (transform, line_light): QueryItem<'_, Self::QueryData>,
     ) -> Option<Self::Out> {
-        // FIXME: don't do computations in extract
-        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
-        let transform_no_scale =
-            Affine3A::from_scale_rotation_translation(scale.signum(), rotation, translation);
-        let affine = Affine3::from(&transform_no_scale);
-        let (a, b) = affine.inverse_transpose_3x3();
-
-        Some((
-            ExtractLineLight2d {
-                world_from_local: affine.to_transpose(),
-                local_from_world_transpose_a: a,
-                local_from_world_transpose_b: b,
-                color: line_light.color,
-                half_length: line_light.half_length,
-                radius: line_light.radius,
-                volumetric_intensity: line_light.volumetric_intensity,
-            },
-            LineLight2dBounds {
-                transform: transform.compute_transform(),
-                half_length: line_light.half_length,
-                radius: line_light.radius,
-            },
-        ))
+        Some(extract_line_light_2d(transform, line_light))
+    }
+}
+
+fn extract_line_light_2d(
+    transform: &GlobalTransform,
+    line_light: &LineLight2d,
+) -> (ExtractLineLight2d, LineLight2dBounds) {
+    // FIXME: don't do computations in extract
+    let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+    let transform_no_scale =
+        Affine3A::from_scale_rotation_translation(scale.signum(), rotation, translation);
+    let affine = Affine3::from(&transform_no_scale);
+    let (a, b) = affine.inverse_transpose_3x3();
+
+    (
+        ExtractLineLight2d {
+            world_from_local: affine.to_transpose(),
+            local_from_world_transpose_a: a,
+            local_from_world_transpose_b: b,
+            color: line_light.color,
+            half_length: line_light.half_length,
+            radius: line_light.radius,
+            volumetric_intensity: line_light.volumetric_intensity,
+        },
+        LineLight2dBounds {
+            transform: transform.compute_transform(),
+            half_length: line_light.half_length,
+            radius: line_light.radius,
+        },
+    )
+}
+
+/// Spawns a temporary render world light for each light of every visible [`LineLight2dBuffer`].
+#[allow(clippy::type_complexity)]
+pub fn extract_line_light_2d_buffers(
+    mut commands: Commands,
+    q_buffers: Extract<
+        Query<(
+            Entity,
+            &GlobalTransform,
+            &InheritedVisibility,
+            &LineLight2dBuffer,
+            Option<&Occluder2dGroups>,
+        )>,
+    >,
+) {
+    for (entity, transform, visibility, buffer, groups) in q_buffers.iter() {
+        if !visibility.get() {
+            continue;
+        }
+        let groups = groups.copied().unwrap_or_default();
+        for (light_transform, line_light) in buffer.lights.iter() {
+            let (extracted, bounds) =
+                extract_line_light_2d(&transform.mul_transform(*light_transform), line_light);
+            commands.spawn((
+                extracted,
+                bounds,
+                groups,
+                BufferedLineLight2d(entity),
+                TemporaryRenderEntity,
+            ));
+        }
     }
 }
 
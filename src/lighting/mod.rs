@@ -11,7 +11,7 @@ use bevy::{
 };
 
 pub use ambient_light::AmbientLight2d;
-pub use line_light::LineLight2d;
+pub use line_light::{LineLight2d, LineLight2dBuffer};
 pub use occluder::{Occluder2d, Occluder2dGroups};
 
 use ambient_light::AmbientLight2dPlugin;
@@ -30,8 +30,8 @@ use bevy::{
 use super::{
     ambient_light::{AmbientLight2dPipeline, SetAmbientLight2dBindGroup},
     line_light::{
-        DrawLineLight2d, ExtractLineLight2d, LineLight2dBounds, LineLight2dPipeline,
-        SetLineLight2dBindGroup,
+        BufferedLineLight2d, DrawLineLight2d, ExtractLineLight2d, LineLight2dBounds,
+        LineLight2dPipeline, SetLineLight2dBindGroup,
     },
     occluder::{
         DrawOccluder2d, ExtractOccluder2d, Occluder2dBounds, Occluder2dGroups, Occluder2dPipeline,
@@ -149,6 +149,7 @@ pub fn queue_deferred_lighting(
     line_light_pipeline: Res<LineLight2dPipeline>,
     ambient_light_pipeline: Res<AmbientLight2dPipeline>,
     q_line_lights: Query<(&LineLight2dBounds, Option<&Occluder2dGroups>), With<ExtractLineLight2d>>,
+    q_buffered_line_lights: Query<(Entity, &BufferedLineLight2d)>,
     q_occluder: Query<(&Occluder2dBounds, Option<&Occluder2dGroups>), With<ExtractOccluder2d>>,
     mut deferred_lighting_phases: ResMut<ViewSortedRenderPhases<DeferredLighting2d>>,
     views: Query<(Entity, &MainEntity, &RenderVisibleEntities), With<AmbientLight2d>>,
@@ -211,7 +212,17 @@ pub fn queue_deferred_lighting(
         );
 
         // Start rendering lights
-        for (pl_e, pl_me) in visible_entities.iter::<With<LineLight2d>>() {
+        // buffered lights only live in the render world, so they use the entity of their buffer
+        let lights: Vec<(Entity, MainEntity)> = visible_entities
+            .iter::<With<LineLight2d>>()
+            .copied()
+            .chain(
+                q_buffered_line_lights
+                    .iter()
+                    .map(|(entity, buffered)| (entity, MainEntity::from(buffered.0))),
+            )
+            .collect();
+        for (pl_e, pl_me) in lights.iter() {
             let Ok((light_bounds, light_group)) = q_line_lights.get(*pl_e) else {
                 continue;
             };
@@ -1,4 +1,5 @@
 use animation::SpriteAnimationPlugin;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
 use bevy::window::PresentMode;
 use bevy::{asset::AssetMetaCheck, diagnostic::LogDiagnosticsPlugin};
@@ -6,16 +7,23 @@ use bevy_rapier2d::prelude::*;
 
 use camera::{CameraPlugin, HIGHRES_LAYER};
 use config::ConfigPlugin;
+use cutscene::CutscenePlugin;
 use debug::DebugPlugin;
-use input::{init_cursor_world_coords, update_cursor_world_coords};
+use input::{
+    init_cursor_world_coords, update_cursor_world_coords, update_held_actions, HeldActions,
+};
 use level::LevelManagementPlugin;
 use light::LightManagementPlugin;
 use lighting::DeferredLightingPlugin;
 use particle::ParticlePlugin;
 use player::PlayerManagementPlugin;
+use save::SavePlugin;
 use shared::{AnimationState, GameState, ResetLevel, UiState};
 use sound::SoundPlugin;
+use ui::dialogue::DialoguePlugin;
+use ui::hint::HintPlugin;
 use ui::level_select::LevelSelectPlugin;
+use ui::localization::LocalizationPlugin;
 use ui::pause::PausePlugin;
 use ui::settings::SettingsPlugin;
 use ui::start_menu::StartMenuPlugin;
@@ -23,6 +31,7 @@ use ui::start_menu::StartMenuPlugin;
 mod animation;
 mod camera;
 mod config;
+mod cutscene;
 mod debug;
 mod input;
 mod level;
@@ -30,6 +39,7 @@ mod light;
 mod lighting;
 mod particle;
 mod player;
+mod save;
 mod shared;
 mod sound;
 mod ui;
@@ -80,7 +90,12 @@ fn main() {
         .add_plugins(PausePlugin)
         .add_plugins(StartMenuPlugin)
         .add_plugins(LevelSelectPlugin)
+        .add_plugins(LocalizationPlugin)
         .add_plugins(SettingsPlugin)
+        .add_plugins(DialoguePlugin)
+        .add_plugins(CutscenePlugin)
+        .add_plugins(HintPlugin)
+        .add_plugins(SavePlugin)
         .add_plugins(CameraPlugin)
         .add_plugins(DebugPlugin::default())
         .insert_state(GameState::Ui)
@@ -91,5 +106,7 @@ fn main() {
         .add_event::<ResetLevel>()
         .add_systems(Startup, init_cursor_world_coords)
         .add_systems(Update, update_cursor_world_coords)
+        .init_resource::<HeldActions>()
+        .add_systems(PreUpdate, update_held_actions.after(InputSystem))
         .run();
 }
@@ -2,7 +2,9 @@ use bevy::{math::vec2, prelude::*};
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    animation::AnimationConfig, input::CursorWorldCoords, level::platform::cast_player_ray_shape,
+    animation::AnimationConfig,
+    input::{CursorWorldCoords, HeldAction, HeldActions},
+    level::platform::cast_player_ray_shape,
     shared::GroupLabel,
 };
 
@@ -92,7 +94,7 @@ pub fn flip_player_direction(
         ),
         With<PlayerMarker>,
     >,
-    buttons: Res<ButtonInput<MouseButton>>,
+    held_actions: Res<HeldActions>,
     q_cursor: Query<&CursorWorldCoords>,
 ) {
     let Ok((mut player_sprite, player_controller_output, player_transform, player_light_inventory)) =
@@ -104,7 +106,7 @@ pub fn flip_player_direction(
         return;
     };
 
-    if buttons.pressed(MouseButton::Left) && player_light_inventory.can_shoot() {
+    if held_actions.active(HeldAction::Shoot) && player_light_inventory.can_shoot() {
         let to_cursor = cursor_coords.pos - player_transform.translation().xy();
         player_sprite.flip_x = to_cursor.x < 0.0;
         return;
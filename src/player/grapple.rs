@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    config::Config,
+    level::LevelSystems,
+    light::{segments::PrevLightBeamPlayback, LightBeamSource},
+};
+
+use super::{
+    light::PlayerBeam,
+    movement::{move_player, PlayerMovement},
+    not_input_locked, PlayerMarker,
+};
+
+/// Distance the grapple pulls the player every [`FixedUpdate`].
+const GRAPPLE_SPEED: f32 = 4.0;
+/// How far from the end point of the beam the pull stops, so the player doesn't end up inside the
+/// wall the beam hit.
+const GRAPPLE_STOP_DISTANCE: f32 = 8.0;
+/// How far from the line of the beam's first segment the player can be for the grapple to catch.
+const GRAPPLE_ATTACH_DISTANCE: f32 = 12.0;
+/// Longest a single pull can take before it gives up.
+const GRAPPLE_MAX_DURATION: Duration = Duration::from_secs(3);
+
+/// [`Plugin`] for the light-grapple, which pulls the player along their most recent beam to the
+/// first point it hits. Unlocked by crystal shards with the `unlocks_grapple` field.
+pub struct PlayerGrapplePlugin;
+
+impl Plugin for PlayerGrapplePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                start_grapple.run_if(grapple_key_just_pressed),
+                cancel_grapple.run_if(jump_key_just_pressed),
+            )
+                .chain()
+                .run_if(not_input_locked)
+                .in_set(LevelSystems::Simulation),
+        )
+        .add_systems(Update, cancel_grapple.in_set(LevelSystems::Reset))
+        .add_systems(
+            FixedUpdate,
+            pull_player
+                .after(move_player)
+                .before(PhysicsSet::SyncBackend)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+/// [`Component`] on the player storing whether the light-grapple is unlocked and the current pull.
+#[derive(Component, Default, Debug)]
+pub struct LightGrapple {
+    pub unlocked: bool,
+    /// Where the current pull ends, if the player is being pulled
+    target: Option<Vec2>,
+    /// Direction of the beam segment the player is pulled along
+    dir: Vec2,
+    /// How long the current pull has been going for
+    elapsed: Duration,
+}
+
+fn grapple_key_just_pressed(keys: Res<ButtonInput<KeyCode>>, config: Res<Config>) -> bool {
+    keys.just_pressed(config.controls_config.key_grapple)
+}
+
+fn jump_key_just_pressed(keys: Res<ButtonInput<KeyCode>>, config: Res<Config>) -> bool {
+    keys.just_pressed(config.controls_config.key_jump)
+}
+
+/// [`System`] that starts pulling the player along the first segment of the beam they shot most
+/// recently, up to the first point it hits. The player has to be close to that segment, so they
+/// only travel where the beam went.
+pub fn start_grapple(
+    mut q_player: Query<(&GlobalTransform, &mut LightGrapple), With<PlayerMarker>>,
+    q_beams: Query<(&PlayerBeam, &LightBeamSource, &PrevLightBeamPlayback)>,
+) {
+    let Ok((player_transform, mut grapple)) = q_player.get_single_mut() else {
+        return;
+    };
+    if !grapple.unlocked {
+        return;
+    }
+
+    let Some((_, source, playback)) = q_beams
+        .iter()
+        .max_by_key(|(player_beam, _, _)| player_beam.shot_at)
+    else {
+        return;
+    };
+    let Some(Some(intersection)) = playback.intersections.first() else {
+        return;
+    };
+
+    let segment = intersection.point - source.start_pos;
+    let Some(dir) = segment.try_normalize() else {
+        return;
+    };
+    let player_offset = player_transform.translation().xy() - source.start_pos;
+    let along = player_offset.dot(dir);
+    let across = player_offset.perp_dot(dir).abs();
+    let end = segment.length() - GRAPPLE_STOP_DISTANCE;
+    if across > GRAPPLE_ATTACH_DISTANCE || along < -GRAPPLE_ATTACH_DISTANCE || along >= end {
+        return;
+    }
+
+    grapple.target = Some(source.start_pos + dir * end);
+    grapple.dir = dir;
+    grapple.elapsed = Duration::ZERO;
+}
+
+/// [`System`] that stops the current pull. Runs when the player jumps and when the level resets.
+pub fn cancel_grapple(mut q_player: Query<&mut LightGrapple, With<PlayerMarker>>) {
+    let Ok(mut grapple) = q_player.get_single_mut() else {
+        return;
+    };
+    grapple.target = None;
+}
+
+/// [`System`] that moves the player along the grapple's beam segment through the
+/// [`KinematicCharacterController`], overriding [`move_player`]. Collisions, crushing and hurt
+/// boxes are handled like any other movement. The pull stops once the player arrives, gets
+/// blocked, or takes too long.
+pub fn pull_player(
+    mut q_player: Query<
+        (
+            &GlobalTransform,
+            &mut KinematicCharacterController,
+            &KinematicCharacterControllerOutput,
+            &mut PlayerMovement,
+            &mut LightGrapple,
+        ),
+        With<PlayerMarker>,
+    >,
+    time: Res<Time>,
+) {
+    let Ok((player_transform, mut controller, output, mut movement, mut grapple)) =
+        q_player.get_single_mut()
+    else {
+        return;
+    };
+    let Some(target) = grapple.target else {
+        return;
+    };
+
+    grapple.elapsed += time.delta();
+    // the previous pull step barely moved the player, so something is in the way
+    let blocked = grapple.elapsed > time.delta()
+        && output.effective_translation.length() < output.desired_translation.length() * 0.25;
+    if blocked || grapple.elapsed > GRAPPLE_MAX_DURATION {
+        grapple.target = None;
+        return;
+    }
+
+    let remaining = (target - player_transform.translation().xy()).dot(grapple.dir);
+    let step = if remaining <= GRAPPLE_SPEED {
+        grapple.target = None;
+        grapple.dir * remaining.max(0.0)
+    } else {
+        grapple.dir * GRAPPLE_SPEED
+    };
+
+    // gravity shouldn't build up during the pull
+    movement.velocity = Vec2::ZERO;
+    controller.translation = Some(step);
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use bevy::{ecs::system::SystemId, input::common_conditions::input_just_pressed, prelude::*};
+use bevy::{ecs::system::SystemId, prelude::*};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
@@ -9,9 +9,11 @@ use crate::{
         camera_position_from_level, CameraControlType, CameraMoveEvent, CameraTransition,
         CameraTransitionEvent,
     },
+    config::Config,
+    input::{HeldAction, HeldActions},
     level::{
-        entity::HurtMarker, shard::reset_shard_effects_on_kill, start_flag::StartFlag,
-        CurrentLevel, LevelSystems,
+        checkpoint::ActiveCheckpoint, entity::HurtMarker, entity_world_pos,
+        snapshot::reset_level_state, start_flag::StartFlag, CurrentLevel, LevelSystems,
     },
     shared::{AnimationState, GameState, ResetLevel, LYRA_RESPAWN_EPSILON},
 };
@@ -33,12 +35,12 @@ impl Plugin for PlayerKillPlugin {
                 Update,
                 (
                     quick_reset
-                        .run_if(input_just_pressed(KeyCode::KeyR))
+                        .run_if(quick_reset_key_just_pressed)
                         .run_if(in_state(GameState::Playing)),
                     // reset player will try to preserve the current color, the calculations for
                     // which depend on proper values for the current level's allowed colors
                     reset_player_on_level_switch
-                        .after(reset_shard_effects_on_kill)
+                        .after(reset_level_state)
                         .in_set(LevelSystems::Reset),
                 ),
             )
@@ -54,7 +56,11 @@ impl Plugin for PlayerKillPlugin {
     }
 }
 
-/// [`System`] that will kill the player on press of the R key
+fn quick_reset_key_just_pressed(keys: Res<ButtonInput<KeyCode>>, config: Res<Config>) -> bool {
+    keys.just_pressed(config.controls_config.key_quick_reset)
+}
+
+/// [`System`] that will kill the player on press of the quick reset key
 pub fn quick_reset(mut ev_kill_player: EventWriter<KillPlayerEvent>) {
     ev_kill_player.send(KillPlayerEvent);
 }
@@ -75,12 +81,14 @@ pub fn play_death_sound_on_kill(
 
 /// [`System`] that runs on [`GameState::Respawning`]. Will turn the state back into playing
 /// immediately.
+#[allow(clippy::too_many_arguments)]
 pub fn reset_player_on_kill(
     mut commands: Commands,
     // angle marker despawn should realistically happen in a diff system?
     q_angle_marker: Query<Entity, With<AngleMarker>>,
     mut ev_reset_level: EventReader<ResetLevel>,
     q_start_flag: Query<(&StartFlag, &EntityInstance)>,
+    active_checkpoint: Res<ActiveCheckpoint>,
     current_level: Res<CurrentLevel>,
     mut ev_move_camera: EventWriter<CameraMoveEvent>,
     mut q_player: Query<&mut Transform, With<PlayerMarker>>,
@@ -97,32 +105,38 @@ pub fn reset_player_on_kill(
         commands.entity(angle_marker).despawn_recursive();
     }
 
-    for (flag, instance) in q_start_flag.iter() {
-        if current_level.level_iid == flag.level_iid {
-            player_transform.translation.x =
-                instance.world_x.expect("Lightborne uses Free world layout") as f32;
-            player_transform.translation.y =
-                -instance.world_y.expect("Lightborne uses Free world layout") as f32
-                    + LYRA_RESPAWN_EPSILON;
-            // add small height so Lyra is not stuck into the floor
-            ev_move_camera.send(CameraMoveEvent {
-                to: camera_position_from_level(
-                    current_level.level_box,
-                    player_transform.translation.xy(),
-                ),
-                variant: CameraControlType::Instant,
-            });
-            return;
-        }
-    }
+    // respawn at the last checkpoint reached in this level, or at the start flag
+    let Some(respawn_pos) = active_checkpoint
+        .respawn_pos(&current_level.level_iid)
+        .or_else(|| {
+            q_start_flag
+                .iter()
+                .find(|(flag, _)| flag.level_iid == current_level.level_iid)
+                .map(|(_, instance)| entity_world_pos(instance))
+        })
+    else {
+        // leave the player where they are rather than crashing the game
+        error!(
+            "Couldn't find start flag to respawn at in level {}",
+            current_level.level_iid
+        );
+        return;
+    };
 
-    panic!("Couldn't find start flag to respawn at");
+    player_transform.translation.x = respawn_pos.x;
+    // add small height so Lyra is not stuck into the floor
+    player_transform.translation.y = respawn_pos.y + LYRA_RESPAWN_EPSILON;
+    ev_move_camera.send(CameraMoveEvent {
+        to: camera_position_from_level(current_level.level_box, player_transform.translation.xy()),
+        variant: CameraControlType::Instant,
+    });
 }
 
 /// Resets the player inventory and movement information on a [`LevelSwitchEvent`]
 pub fn reset_player_on_level_switch(
     mut q_player: Query<(&mut PlayerMovement, &mut PlayerLightInventory), With<PlayerMarker>>,
     current_level: Res<CurrentLevel>,
+    mut held_actions: ResMut<HeldActions>,
 ) {
     let Ok((mut movement, mut inventory)) = q_player.get_single_mut() else {
         return;
@@ -131,7 +145,8 @@ pub fn reset_player_on_level_switch(
     let old_color = inventory.current_color;
 
     *movement = PlayerMovement::default();
-    *inventory = PlayerLightInventory::new();
+    *inventory = PlayerLightInventory::new(current_level.beam_counts);
+    held_actions.cancel(HeldAction::Shoot);
 
     // if the new level has the current color as an allowed color, preserve it
     if let Some(color) = old_color {
@@ -192,7 +207,10 @@ pub fn start_kill_animation(
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_anim_state: ResMut<NextState<AnimationState>>,
 ) {
-    if *cur_game_state.get() == GameState::Animating {
+    if matches!(
+        cur_game_state.get(),
+        GameState::Animating | GameState::Cutscene
+    ) {
         return;
     }
     ev_transition_camera.send(CameraTransitionEvent {
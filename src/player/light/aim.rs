@@ -0,0 +1,146 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::{
+        ops::{cos, sin},
+        *,
+    },
+};
+use bevy_rapier2d::plugin::RapierContext;
+use std::f32::consts::PI;
+
+use super::keyboard_aim::KeyboardAim;
+use crate::{
+    input::{HeldAction, HeldActions},
+    level::{sensor::LightSensor, surface::SurfaceMaterials},
+    light::{
+        segments::{play_light_beam, LightBeamPlayback},
+        BlackRayComponent, LightBeamSource, LightColor,
+    },
+};
+
+/// The number of angle increments for light beam alignment when snapping with [`HeldAction::Snap`].
+const NUMINCREMENTS: i32 = 16;
+
+/// How far aim assist can bend the aimed direction, in radians.
+const AIM_ASSIST_TOLERANCE: f32 = PI / 30.0;
+
+/// The angle between the directions aim assist tries, in radians.
+const AIM_ASSIST_STEP: f32 = PI / 360.0;
+
+/// How the aimed direction snaps to fixed angles.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AngleSnapping {
+    /// Never snaps, even during [`HeldAction::Snap`]
+    Off,
+    /// Always snaps to this many directions
+    Increments(i32),
+    /// Aims freely, snapping to [`NUMINCREMENTS`] directions during [`HeldAction::Snap`]
+    #[default]
+    Free,
+}
+
+impl AngleSnapping {
+    /// The number of directions the aim snaps to while [`HeldAction::Snap`] is `snap_held` or not,
+    /// or [`None`] if it doesn't snap.
+    pub fn increments(&self, snap_held: bool) -> Option<i32> {
+        match self {
+            AngleSnapping::Increments(increments) => Some(*increments),
+            AngleSnapping::Free if snap_held => Some(NUMINCREMENTS),
+            _ => None,
+        }
+    }
+}
+
+/// [`Resource`] holding the aiming options picked in the settings menu.
+#[derive(Resource, Default, Debug)]
+pub struct AimSettings {
+    pub snapping: AngleSnapping,
+    /// Bends the aim toward sensors that aren't activated yet
+    pub aim_assist: bool,
+}
+
+/// [`SystemParam`] used to turn where the player is aiming into the direction of the beam they
+/// would shoot, applying the [`AimSettings`].
+#[derive(SystemParam)]
+pub struct BeamAim<'w, 's> {
+    q_rapier: Query<'w, 's, &'static mut RapierContext>,
+    q_black_ray: Query<'w, 's, (Entity, &'static BlackRayComponent)>,
+    q_sensors: Query<'w, 's, &'static LightSensor>,
+    surfaces: SurfaceMaterials<'w, 's>,
+    held_actions: Res<'w, HeldActions>,
+    settings: Res<'w, AimSettings>,
+    keyboard_aim: Res<'w, KeyboardAim>,
+}
+
+impl BeamAim<'_, '_> {
+    /// Traces the full path a beam shot from `source` would take.
+    pub fn trace(&mut self, source: &LightBeamSource) -> Option<LightBeamPlayback> {
+        let rapier_context = self.q_rapier.get_single_mut().ok()?;
+        Some(play_light_beam(
+            rapier_context.into_inner(),
+            source,
+            &self.q_black_ray,
+            &self.surfaces,
+        ))
+    }
+
+    /// The direction of a beam of `color` shot from `ray_pos` toward `cursor`, or toward the
+    /// [`KeyboardAim`] if the keyboard is being used to aim.
+    pub fn direction(&mut self, ray_pos: Vec2, cursor: Vec2, color: LightColor) -> Vec2 {
+        let target = self.keyboard_aim.target(ray_pos).unwrap_or(cursor);
+        let ray_dir = (target - ray_pos).normalize_or_zero();
+        if ray_dir == Vec2::ZERO {
+            return ray_dir;
+        }
+
+        let snap_held = self.held_actions.active(HeldAction::Snap);
+        if let Some(increments) = self.settings.snapping.increments(snap_held) {
+            return snap_ray(ray_dir, increments);
+        }
+
+        if self.settings.aim_assist {
+            return self.assist(ray_pos, ray_dir, color).unwrap_or(ray_dir);
+        }
+        ray_dir
+    }
+
+    /// Finds the direction closest to `ray_dir`, within [`AIM_ASSIST_TOLERANCE`], that hits a
+    /// [`LightSensor`] that isn't active yet.
+    fn assist(&mut self, ray_pos: Vec2, ray_dir: Vec2, color: LightColor) -> Option<Vec2> {
+        let steps = (AIM_ASSIST_TOLERANCE / AIM_ASSIST_STEP) as i32;
+        std::iter::once(0)
+            .chain((1..=steps).flat_map(|i| [i, -i]))
+            .map(|i| Vec2::from_angle(i as f32 * AIM_ASSIST_STEP).rotate(ray_dir))
+            .find(|dir| {
+                self.hits_inactive_sensor(&LightBeamSource {
+                    start_pos: ray_pos,
+                    start_dir: *dir,
+                    time_traveled: 10000.0,
+                    color,
+                })
+            })
+    }
+
+    fn hits_inactive_sensor(&mut self, source: &LightBeamSource) -> bool {
+        let Some(playback) = self.trace(source) else {
+            return false;
+        };
+        let mut prev_point = source.start_pos;
+        playback.intersections.iter().any(|intersection| {
+            let beam_dir = (intersection.point - prev_point).normalize_or_zero();
+            prev_point = intersection.point;
+            self.q_sensors
+                .get(intersection.entity)
+                .is_ok_and(|sensor| !sensor.is_active && sensor.accepts(beam_dir))
+        })
+    }
+}
+
+/// Snaps `ray_vec` to the closest of `increments` evenly spaced directions.
+fn snap_ray(ray_vec: Vec2, increments: i32) -> Vec2 {
+    let ray_angle = (ray_vec.y.atan2(ray_vec.x) + (2.0 * PI)) % (2.0 * PI);
+    let increment_angle = (2.0 * PI) / increments as f32;
+    let snapped_angle = (ray_angle / increment_angle).round() * increment_angle;
+
+    Vec2::new(cos(snapped_angle), sin(snapped_angle))
+}
@@ -94,7 +94,7 @@ pub fn update_light_indicator(
     };
 
     if let Some(color) = inventory.current_color {
-        let material = match inventory.sources[color] {
+        let material = match inventory.sources[color] > 0 {
             false => light_data.dimmed_material_map[color].clone(),
             true => light_data.material_map[color].clone(),
         };
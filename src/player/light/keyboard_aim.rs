@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+use crate::{config::Config, player::PlayerMarker};
+
+use super::{
+    aim::{AimSettings, AngleSnapping},
+    AngleMarker, PlayerLightInventory,
+};
+
+/// How far from Lyra the keyboard aim points, so that it can stand in for the cursor.
+const KEYBOARD_AIM_DISTANCE: f32 = 64.0;
+
+/// [`Resource`] tracking the direction aimed with the keyboard, for players who can't use a
+/// mouse. Keyboard aiming takes over from the cursor as soon as an aim key is pressed, and gives it
+/// back when the left mouse button is pressed. Only the rotate keys start keyboard aiming, so the
+/// fire key never shoots in a stale direction.
+#[derive(Resource, Debug)]
+pub struct KeyboardAim {
+    active: bool,
+    /// Aimed angle in radians, counter-clockwise from the right
+    angle: f32,
+}
+
+impl Default for KeyboardAim {
+    fn default() -> Self {
+        KeyboardAim {
+            active: false,
+            angle: PI / 2.0,
+        }
+    }
+}
+
+impl KeyboardAim {
+    /// The point the keyboard is aiming at from `origin`, if the keyboard is being used to aim.
+    pub fn target(&self, origin: Vec2) -> Option<Vec2> {
+        self.active
+            .then(|| origin + Vec2::from_angle(self.angle) * KEYBOARD_AIM_DISTANCE)
+    }
+}
+
+pub fn keyboard_aim_just_pressed(keys: Res<ButtonInput<KeyCode>>, config: Res<Config>) -> bool {
+    keys.any_just_pressed([
+        config.controls_config.key_aim_ccw,
+        config.controls_config.key_aim_cw,
+    ])
+}
+
+pub fn keyboard_fire_just_pressed(
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Res<Config>,
+    keyboard_aim: Res<KeyboardAim>,
+) -> bool {
+    keyboard_aim.active && keys.just_pressed(config.controls_config.key_aim_fire)
+}
+
+/// [`System`] that rotates the keyboard aim while an aim key is held, at
+/// [`aim_speed`](crate::config::ControlsConfig::aim_speed) degrees per second. When angle
+/// snapping is on, each press steps to the next snapped direction instead.
+pub fn rotate_keyboard_aim(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    config: Res<Config>,
+    aim_settings: Res<AimSettings>,
+    time: Res<Time>,
+    mut keyboard_aim: ResMut<KeyboardAim>,
+    mut q_inventory: Query<&mut PlayerLightInventory, With<PlayerMarker>>,
+) {
+    let controls = &config.controls_config;
+    if mouse.just_pressed(MouseButton::Left) {
+        keyboard_aim.active = false;
+        return;
+    }
+
+    if keys.any_just_pressed([controls.key_aim_ccw, controls.key_aim_cw]) {
+        keyboard_aim.active = true;
+        if let Ok(mut inventory) = q_inventory.get_single_mut() {
+            inventory.should_shoot = true;
+        }
+    }
+    if !keyboard_aim.active {
+        return;
+    }
+
+    let (ccw, cw) = (controls.key_aim_ccw, controls.key_aim_cw);
+    match aim_settings.snapping {
+        AngleSnapping::Increments(increments) => {
+            let steps = keys.just_pressed(ccw) as i32 - keys.just_pressed(cw) as i32;
+            keyboard_aim.angle += steps as f32 * 2.0 * PI / increments as f32;
+        }
+        _ => {
+            let turn = keys.pressed(ccw) as i32 - keys.pressed(cw) as i32;
+            let speed = controls.aim_speed.to_radians();
+            keyboard_aim.angle += turn as f32 * speed * time.delta_secs();
+        }
+    }
+    keyboard_aim.angle = keyboard_aim.angle.rem_euclid(2.0 * PI);
+}
+
+/// [`System`] that turns the angle indicator spawned by
+/// [`spawn_angle_indicator`](super::spawn_angle_indicator) toward the direction aimed with the
+/// keyboard, and makes it stand out while the keyboard is used to aim.
+pub fn point_angle_indicator(
+    keyboard_aim: Res<KeyboardAim>,
+    mut q_indicator: Query<(&mut Transform, &mut Sprite), With<AngleMarker>>,
+) {
+    let (rotation, alpha) = match keyboard_aim.active {
+        true => (Quat::from_rotation_z(keyboard_aim.angle), 0.6),
+        false => (Quat::IDENTITY, 0.1),
+    };
+    for (mut transform, mut sprite) in q_indicator.iter_mut() {
+        transform.rotation = rotation;
+        sprite.color.set_alpha(alpha);
+    }
+}
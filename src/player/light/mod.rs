@@ -1,74 +1,86 @@
+use aim::{AimSettings, BeamAim};
 use bevy::{
-    input::{
-        common_conditions::{input_just_pressed, input_just_released, input_pressed},
-        mouse::MouseWheel,
-    },
+    input::{common_conditions::input_just_pressed, mouse::MouseWheel},
     prelude::*,
 };
-use bevy_rapier2d::plugin::RapierContext;
-use enum_map::{enum_map, EnumMap};
-use itertools::Itertools;
+use enum_map::EnumMap;
+use keyboard_aim::{
+    keyboard_aim_just_pressed, keyboard_fire_just_pressed, point_angle_indicator,
+    rotate_keyboard_aim, KeyboardAim,
+};
+use preview::{preview_light_path, LightPreviewPlugin};
 use ui::LightUiPlugin;
 
-use bevy::prelude::ops::{cos, sin};
-use std::f32::consts::PI;
+use std::{f32::consts::PI, time::Duration};
 
 use crate::{
     camera::{HIGHRES_LAYER, TERRAIN_LAYER},
-    input::{update_cursor_world_coords, CursorWorldCoords},
-    level::{mirror::Mirror, CurrentLevel, LevelSystems},
-    light::{
-        segments::{play_light_beam, PrevLightBeamPlayback},
-        BlackRayComponent, LightBeamSource, LightColor, LightSourceZMarker,
+    config::Config,
+    input::{
+        held_action_active, held_action_just_ended, held_action_just_started,
+        update_cursor_world_coords, CursorWorldCoords, HeldAction, HeldActions,
     },
+    level::{CurrentLevel, LevelSystems},
+    light::{segments::PrevLightBeamPlayback, LightBeamSource, LightColor, LightSourceZMarker},
     lighting::LineLight2d,
 };
 use indicator::LightIndicatorPlugin;
+use recall::BeamRecallPlugin;
 
+pub mod aim;
 mod indicator;
+mod keyboard_aim;
+mod preview;
+mod recall;
 mod ui;
 
-const NUMINCREMENTS: i32 = 16; // The number of angle increments for light beam alignment
-
 use super::{not_input_locked, PlayerMarker};
 
 pub struct PlayerLightPlugin;
 
 impl Plugin for PlayerLightPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(LightIndicatorPlugin)
+        app.init_resource::<AimSettings>()
+            .init_resource::<KeyboardAim>()
+            .add_plugins(LightIndicatorPlugin)
             .add_plugins(LightUiPlugin)
+            .add_plugins(BeamRecallPlugin)
+            .add_plugins(LightPreviewPlugin)
             .add_systems(
                 Update,
                 (
                     handle_color_switch,
-                    should_shoot_light::<true>.run_if(input_just_pressed(MouseButton::Left)),
+                    should_shoot_light::<true>.run_if(held_action_just_started(HeldAction::Shoot)),
                     should_shoot_light::<false>.run_if(input_just_pressed(MouseButton::Right)),
+                    rotate_keyboard_aim,
                     preview_light_path,
                     spawn_angle_indicator.run_if(
-                        input_just_pressed(MouseButton::Left).or((input_just_released(
-                            KeyCode::ShiftLeft,
-                        )
-                        .or(input_just_released(KeyCode::ShiftRight)))
-                        .and(input_pressed(MouseButton::Left))),
+                        (held_action_just_started(HeldAction::Shoot).or(keyboard_aim_just_pressed))
+                            .and(not(held_action_active(HeldAction::Snap)))
+                            .or(held_action_just_ended(HeldAction::Snap)
+                                .and(held_action_active(HeldAction::Shoot))),
                     ),
                     despawn_angle_indicator.run_if(
-                        input_just_released(MouseButton::Left)
+                        held_action_just_ended(HeldAction::Shoot)
                             .or(input_just_pressed(MouseButton::Right))
-                            .or(input_just_pressed(KeyCode::ShiftLeft))
-                            .or(input_just_pressed(KeyCode::ShiftRight)),
+                            .or(held_action_just_started(HeldAction::Snap))
+                            .or(keyboard_fire_just_pressed),
                     ),
+                    point_angle_indicator,
                     spawn_angle_increments_indicators.run_if(
-                        input_just_pressed(KeyCode::ShiftLeft)
-                            .or(input_just_pressed(KeyCode::ShiftRight))
-                            .and(input_pressed(MouseButton::Left)),
+                        held_action_just_started(HeldAction::Snap)
+                            .and(held_action_active(HeldAction::Shoot))
+                            .or(held_action_just_started(HeldAction::Shoot)
+                                .and(held_action_active(HeldAction::Snap))),
                     ),
                     despawn_angle_increments_indicators.run_if(
-                        input_just_released(KeyCode::ShiftLeft)
-                            .or(input_just_released(KeyCode::ShiftRight))
+                        held_action_just_ended(HeldAction::Snap)
+                            .or(held_action_just_ended(HeldAction::Shoot))
                             .or(input_just_pressed(MouseButton::Right)),
                     ),
-                    shoot_light.run_if(input_just_released(MouseButton::Left)),
+                    shoot_light.run_if(
+                        held_action_just_ended(HeldAction::Shoot).or(keyboard_fire_just_pressed),
+                    ),
                 )
                     .chain()
                     .run_if(not_input_locked)
@@ -82,33 +94,40 @@ impl Plugin for PlayerLightPlugin {
 /// that color remaining.
 #[derive(Component, Default, Debug)]
 pub struct PlayerLightInventory {
-    /// set to true when LMB is clicked, set to false when RMB is clicked/LMB is released
+    /// set to true when [`HeldAction::Shoot`] starts, set to false when RMB is clicked or the beam
+    /// is shot
     should_shoot: bool,
     pub current_color: Option<LightColor>,
-    /// Is true if the color is available
-    pub sources: EnumMap<LightColor, bool>,
+    /// Number of beams of each color that can still be shot
+    pub sources: EnumMap<LightColor, u32>,
 }
 
 impl PlayerLightInventory {
-    pub fn new() -> Self {
+    /// Creates an inventory with the [`CurrentLevel::beam_counts`] of a level.
+    pub fn new(beam_counts: EnumMap<LightColor, u32>) -> Self {
         PlayerLightInventory {
             should_shoot: false,
             current_color: None,
-            sources: enum_map! {
-                LightColor::Green =>true,
-                LightColor::Blue => true,
-                LightColor::Purple => true,
-                LightColor::White =>true,
-                LightColor::Black => true,
-            },
+            sources: beam_counts,
         }
     }
 
     pub fn can_shoot(&self) -> bool {
-        self.should_shoot && self.current_color.is_some_and(|color| self.sources[color])
+        self.should_shoot
+            && self
+                .current_color
+                .is_some_and(|color| self.sources[color] > 0)
     }
 }
 
+/// [`Component`] marking the [`LightBeamSource`]s shot by the player, as opposed to the ones
+/// spawned by the level.
+#[derive(Component)]
+pub struct PlayerBeam {
+    /// When the beam was shot, used to find the most recent beam
+    pub shot_at: Duration,
+}
+
 #[derive(Component)]
 pub struct AngleMarker;
 
@@ -145,9 +164,17 @@ pub fn spawn_angle_increments_indicators(
     q_player: Query<Entity, With<PlayerMarker>>,
     q_angle: Query<Entity, With<AngleIncrementMarker>>,
     asset_server: Res<AssetServer>,
+    aim_settings: Res<AimSettings>,
+    held_actions: Res<HeldActions>,
 ) {
-    for i in 0..NUMINCREMENTS {
-        let angle_increment = (2.0 * PI) / NUMINCREMENTS as f32;
+    let Some(increments) = aim_settings
+        .snapping
+        .increments(held_actions.active(HeldAction::Snap))
+    else {
+        return;
+    };
+    for i in 0..increments {
+        let angle_increment = (2.0 * PI) / increments as f32;
         let Ok(player) = q_player.get_single() else {
             return;
         };
@@ -190,6 +217,7 @@ pub fn despawn_angle_increments_indicators(
 /// [`System`] to handle the keyboard presses corresponding to color switches.
 pub fn handle_color_switch(
     keys: Res<ButtonInput<KeyCode>>,
+    config: Res<Config>,
     mut ev_scroll: EventReader<MouseWheel>,
     mut q_inventory: Query<&mut PlayerLightInventory, With<PlayerMarker>>,
     current_level: Res<CurrentLevel>,
@@ -198,12 +226,13 @@ pub fn handle_color_switch(
         return;
     };
 
-    static COLOR_BINDS: [(KeyCode, LightColor); 5] = [
-        (KeyCode::Digit1, LightColor::Green),
-        (KeyCode::Digit2, LightColor::Purple),
-        (KeyCode::Digit3, LightColor::White),
-        (KeyCode::Digit4, LightColor::Blue),
-        (KeyCode::Digit5, LightColor::Black),
+    // in the same order as `ControlsConfig::key_colors`
+    static COLORS: [LightColor; 5] = [
+        LightColor::Green,
+        LightColor::Purple,
+        LightColor::White,
+        LightColor::Blue,
+        LightColor::Black,
     ];
 
     let mut cur_index = match inventory.current_color {
@@ -222,19 +251,19 @@ pub fn handle_color_switch(
         // suspicious algorithm to cycle through available colors with the scroll wheel
         // basically skips disallowed colors until you find the next one
         let mut count = 0;
-        while !current_level.allowed_colors[COLOR_BINDS[new_index.rem_euclid(4) as usize].1]
-            && count < COLOR_BINDS.len()
+        while !current_level.allowed_colors[COLORS[new_index.rem_euclid(4) as usize]]
+            && count < COLORS.len()
         {
             new_index += sign;
             count += 1;
         }
         cur_index = new_index;
-        if current_level.allowed_colors[COLOR_BINDS[new_index.rem_euclid(4) as usize].1] {
-            inventory.current_color = Some(COLOR_BINDS[cur_index.rem_euclid(4) as usize].1);
+        if current_level.allowed_colors[COLORS[new_index.rem_euclid(4) as usize]] {
+            inventory.current_color = Some(COLORS[cur_index.rem_euclid(4) as usize]);
         }
     }
 
-    for (key, color) in COLOR_BINDS {
+    for (key, color) in config.controls_config.key_colors.into_iter().zip(COLORS) {
         if keys.just_pressed(key) && current_level.allowed_colors[color] {
             inventory.current_color = Some(color);
         }
@@ -243,20 +272,28 @@ pub fn handle_color_switch(
 
 pub fn should_shoot_light<const V: bool>(
     mut q_player: Query<&mut PlayerLightInventory, With<PlayerMarker>>,
+    mut held_actions: ResMut<HeldActions>,
 ) {
     let Ok(mut inventory) = q_player.get_single_mut() else {
         return;
     };
     inventory.should_shoot = V;
+    if !V {
+        // a toggled shot shouldn't stay armed after being cancelled
+        held_actions.cancel(HeldAction::Shoot);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn shoot_light(
     mut commands: Commands,
     mut q_player: Query<(&Transform, &mut PlayerLightInventory), With<PlayerMarker>>,
     q_light_source_z: Query<&Transform, With<LightSourceZMarker>>,
     q_cursor: Query<&CursorWorldCoords>,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut aim: BeamAim,
+    mut held_actions: ResMut<HeldActions>,
     asset_server: Res<AssetServer>,
+    time: Res<Time>,
 ) {
     let Ok((player_transform, mut player_inventory)) = q_player.get_single_mut() else {
         return;
@@ -271,111 +308,61 @@ pub fn shoot_light(
         return;
     }
 
-    let ray_pos = player_transform.translation.truncate();
-    let mut ray_dir = (cursor_pos.pos - ray_pos).normalize_or_zero();
+    let shoot_color = player_inventory.current_color.unwrap();
 
-    if keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight) {
-        ray_dir = snap_ray(ray_dir);
-    }
+    let ray_pos = player_transform.translation.truncate();
+    let ray_dir = aim.direction(ray_pos, cursor_pos.pos, shoot_color);
 
     if ray_dir == Vec2::ZERO {
         return;
     }
 
-    let shoot_color = player_inventory.current_color.unwrap();
-
-    let mut source_transform =
-        Transform::from_translation(ray_pos.extend(light_source_z.translation.z));
-    source_transform.rotate_z(ray_dir.to_angle());
-    let mut source_sprite = Sprite::from_image(asset_server.load("light/compass.png"));
-    source_sprite.color = Color::srgb(2.0, 2.0, 2.0);
-    let mut outer_source_sprite = Sprite::from_image(asset_server.load("light/compass-gold.png"));
-    outer_source_sprite.color = shoot_color.light_beam_color().mix(&Color::BLACK, 0.4);
-
-    commands
-        .spawn(LightBeamSource {
+    spawn_player_beam(
+        &mut commands,
+        &asset_server,
+        LightBeamSource {
             start_pos: ray_pos,
             start_dir: ray_dir,
             time_traveled: 0.0,
             color: shoot_color,
-        })
-        .insert(PrevLightBeamPlayback::default())
-        .insert(HIGHRES_LAYER)
-        .insert(source_sprite)
-        .insert(source_transform)
-        .with_child((outer_source_sprite, HIGHRES_LAYER))
-        .with_child((
-            LineLight2d::point(shoot_color.lighting_color().extend(1.0), 30.0, 0.02),
-            TERRAIN_LAYER,
-        ));
+        },
+        light_source_z.translation.z,
+        time.elapsed(),
+    );
 
     // Bevy's Mut or ResMut doesn't let you borrow multiple fields of a struct, so sometimes you
     // need to "reborrow" it to turn it into &mut. See https://bevy-cheatbook.github.io/pitfalls/split-borrows.html
     let player_inventory = &mut *player_inventory;
-    player_inventory.sources[shoot_color] = false;
+    player_inventory.sources[shoot_color] -= 1;
     player_inventory.should_shoot = false;
+    // the beam may have been shot from the keyboard while a toggled shot was armed
+    held_actions.cancel(HeldAction::Shoot);
 }
 
-#[derive(Default, Reflect, GizmoConfigGroup)]
-pub struct LightPreviewGizmos;
-
-/// [`System`] that uses [`Gizmos`] to preview the light path while the left mouse button is held
-/// down. This system needs some work, namely:
-///
-/// - Not using [`Gizmos`] to render the light segments
-pub fn preview_light_path(
-    mut q_rapier: Query<&mut RapierContext>,
-    q_player: Query<(&Transform, &PlayerLightInventory), With<PlayerMarker>>,
-    q_cursor: Query<&CursorWorldCoords>,
-    keys: Res<ButtonInput<KeyCode>>,
-    q_mirror: Query<&Mirror>,
-    mut gizmos: Gizmos,
-    q_black_ray: Query<(Entity, &BlackRayComponent)>,
+/// Spawns a beam shot by the player, along with the compass sprite and light drawn where it was
+/// shot from.
+pub fn spawn_player_beam(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    source: LightBeamSource,
+    z: f32,
+    shot_at: Duration,
 ) {
-    let Ok(rapier_context) = q_rapier.get_single_mut() else {
-        return;
-    };
-    let Ok((transform, inventory)) = q_player.get_single() else {
-        return;
-    };
-    let Ok(cursor_pos) = q_cursor.get_single() else {
-        return;
-    };
-    if !inventory.can_shoot() {
-        return;
-    }
-
-    let shoot_color = inventory.current_color.unwrap();
-
-    let ray_pos = transform.translation.truncate();
-    let mut ray_dir = (cursor_pos.pos - ray_pos).normalize_or_zero();
-
-    if keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight) {
-        ray_dir = snap_ray(ray_dir);
-    }
-
-    let dummy_source = LightBeamSource {
-        start_pos: ray_pos,
-        start_dir: ray_dir,
-        time_traveled: 10000.0, // LOL
-        color: shoot_color,
-    };
-    let playback = play_light_beam(
-        rapier_context.into_inner(),
-        &dummy_source,
-        &q_black_ray,
-        &q_mirror,
-    );
-
-    for (a, b) in playback.iter_points(&dummy_source).tuple_windows() {
-        gizmos.line_2d(a, b, shoot_color.light_beam_color().darker(0.3));
-    }
-}
-
-fn snap_ray(ray_vec: Vec2) -> Vec2 {
-    let ray_angle = (ray_vec.y.atan2(ray_vec.x) + (2.0 * PI)) % (2.0 * PI);
-    let increment_angle = (2.0 * PI) / NUMINCREMENTS as f32;
-    let snapped_angle = (ray_angle / increment_angle).round() * increment_angle;
+    let mut source_transform = Transform::from_translation(source.start_pos.extend(z));
+    source_transform.rotate_z(source.start_dir.to_angle());
+    let mut source_sprite = Sprite::from_image(asset_server.load("light/compass.png"));
+    source_sprite.color = Color::srgb(2.0, 2.0, 2.0);
+    let mut outer_source_sprite = Sprite::from_image(asset_server.load("light/compass-gold.png"));
+    outer_source_sprite.color = source.color.light_beam_color().mix(&Color::BLACK, 0.4);
+    let light = LineLight2d::point(source.color.lighting_color().extend(1.0), 30.0, 0.02);
 
-    Vec2::new(cos(snapped_angle), sin(snapped_angle))
+    commands
+        .spawn(source)
+        .insert(PrevLightBeamPlayback::default())
+        .insert(PlayerBeam { shot_at })
+        .insert(HIGHRES_LAYER)
+        .insert(source_sprite)
+        .insert(source_transform)
+        .with_child((outer_source_sprite, HIGHRES_LAYER))
+        .with_child((light, TERRAIN_LAYER));
 }
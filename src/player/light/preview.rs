@@ -0,0 +1,348 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use enum_map::{enum_map, EnumMap};
+
+use crate::{
+    camera::HIGHRES_LAYER,
+    input::CursorWorldCoords,
+    level::{sensor::LightSensor, LevelSystems},
+    light::{
+        render::light_beam_mesh, segments::LightBeamPlayback, BlackRayComponent, LightBeamSource,
+        LightColor, LightSegmentZMarker, LIGHT_SPEED,
+    },
+    player::{not_input_locked, PlayerMarker},
+};
+
+use super::{aim::BeamAim, PlayerLightInventory};
+
+/// Radius of the ring drawn around each [`LightSensor`] the previewed beam would hit.
+const SENSOR_MARKER_RADIUS: f32 = 10.0;
+
+/// [`Plugin`] that draws where the player's next beam would go while they aim it.
+pub struct LightPreviewPlugin;
+
+impl Plugin for LightPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LightPreviewData>()
+            .add_systems(Startup, spawn_light_preview)
+            .add_systems(
+                Update,
+                hide_light_preview
+                    .run_if(not(not_input_locked))
+                    .in_set(LevelSystems::Simulation),
+            );
+    }
+}
+
+/// A resource that stores handles to the meshes and materials used to draw the light preview.
+#[derive(Resource)]
+pub struct LightPreviewData {
+    pub path_material_map: EnumMap<LightColor, MeshMaterial2d<ColorMaterial>>,
+    pub sensor_mesh: Mesh2d,
+    pub sensor_material: MeshMaterial2d<ColorMaterial>,
+    pub end_mesh: Mesh2d,
+    pub blocked_material: MeshMaterial2d<ColorMaterial>,
+    pub out_of_range_material: MeshMaterial2d<ColorMaterial>,
+    pub font: TextFont,
+}
+
+impl FromWorld for LightPreviewData {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let sensor_mesh = meshes
+            .add(Annulus::new(
+                SENSOR_MARKER_RADIUS - 1.0,
+                SENSOR_MARKER_RADIUS,
+            ))
+            .into();
+        let end_mesh = meshes.add(Circle::new(2.5)).into();
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let path_material_map: EnumMap<LightColor, MeshMaterial2d<ColorMaterial>> = enum_map! {
+            val => materials
+                .add(val.light_beam_color().darker(0.3).with_alpha(0.6))
+                .into(),
+        };
+        let sensor_material = materials.add(Color::srgba(1.0, 1.0, 1.0, 0.8)).into();
+        let blocked_material = materials.add(Color::srgb(0.8, 0.1, 0.1)).into();
+        let out_of_range_material = materials.add(Color::srgba(1.0, 1.0, 1.0, 0.3)).into();
+
+        let asset_server = world.resource::<AssetServer>();
+        let font = TextFont {
+            font: asset_server.load("fonts/Munro.ttf"),
+            font_size: 8.0,
+            ..default()
+        };
+
+        LightPreviewData {
+            path_material_map,
+            sensor_mesh,
+            sensor_material,
+            end_mesh,
+            blocked_material,
+            out_of_range_material,
+            font,
+        }
+    }
+}
+
+/// Marker [`Component`] for the parent of every entity that makes up the light preview.
+#[derive(Component)]
+pub struct LightPreviewMarker;
+
+/// Marker [`Component`] for the mesh of the previewed beam.
+#[derive(Component)]
+pub struct LightPreviewPathMarker;
+
+/// Marker [`Component`] for the dot drawn where the previewed beam stops, either because it hit a
+/// black beam or because it flew off without hitting anything.
+#[derive(Component)]
+pub struct LightPreviewEndMarker;
+
+/// [`Component`] for the ring drawn around a [`LightSensor`] the previewed beam would hit. The
+/// [`Text2d`] child shows how long it would take to activate the sensor.
+#[derive(Component)]
+pub struct LightPreviewSensorMarker;
+
+fn spawn_light_preview(mut commands: Commands, preview_data: Res<LightPreviewData>) {
+    commands
+        .spawn((LightPreviewMarker, Transform::default(), Visibility::Hidden))
+        .with_child((
+            LightPreviewPathMarker,
+            Mesh2d::default(),
+            preview_data.path_material_map[LightColor::Green].clone(),
+            Transform::default(),
+            HIGHRES_LAYER,
+        ))
+        .with_child((
+            LightPreviewEndMarker,
+            preview_data.end_mesh.clone(),
+            preview_data.blocked_material.clone(),
+            Transform::default(),
+            Visibility::Hidden,
+            HIGHRES_LAYER,
+        ));
+}
+
+/// [`System`] that hides the light preview while the player can't aim, since
+/// [`preview_light_path`] doesn't run then.
+fn hide_light_preview(mut q_preview: Query<&mut Visibility, With<LightPreviewMarker>>) {
+    for mut visibility in q_preview.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// A [`LightSensor`] that the previewed beam would hit.
+struct PreviewSensorHit {
+    pos: Vec2,
+    /// How long until the sensor would activate after the beam is shot
+    activation_secs: f32,
+}
+
+/// Finds the [`LightSensor`]s hit along `playback`, and how long each would take to activate: the
+/// beam has to travel to it at [`LIGHT_SPEED`] every fixed timestep, then fill its meter.
+fn preview_sensor_hits(
+    playback: &LightBeamPlayback,
+    source: &LightBeamSource,
+    q_sensors: &Query<(&LightSensor, &GlobalTransform)>,
+    timestep_secs: f32,
+) -> Vec<PreviewSensorHit> {
+    let mut prev_point = source.start_pos;
+    let mut hits = vec![];
+    for intersection in playback.intersections.iter() {
+        let beam_dir = (intersection.point - prev_point).normalize_or_zero();
+        prev_point = intersection.point;
+
+        let Ok((sensor, transform)) = q_sensors.get(intersection.entity) else {
+            continue;
+        };
+        if !sensor.accepts(beam_dir) {
+            continue;
+        }
+        let travel_ticks = intersection.time / LIGHT_SPEED;
+        hits.push(PreviewSensorHit {
+            pos: transform.translation().truncate(),
+            activation_secs: (travel_ticks + sensor.ticks_to_fill()) * timestep_secs,
+        });
+    }
+    hits
+}
+
+/// [`SystemParam`] for the assets [`preview_light_path`] draws with.
+#[derive(SystemParam)]
+pub struct LightPreviewAssets<'w> {
+    meshes: ResMut<'w, Assets<Mesh>>,
+    data: Res<'w, LightPreviewData>,
+}
+
+/// [`System`] that previews the path of the next beam while the left mouse button is held down,
+/// highlighting the sensors it would activate and marking where it stops.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn preview_light_path(
+    mut commands: Commands,
+    q_player: Query<(&GlobalTransform, &PlayerLightInventory), With<PlayerMarker>>,
+    q_cursor: Query<&CursorWorldCoords>,
+    mut aim: BeamAim,
+    q_black_ray: Query<(), With<BlackRayComponent>>,
+    q_sensors: Query<(&LightSensor, &GlobalTransform)>,
+    q_light_segment_z: Query<&GlobalTransform, With<LightSegmentZMarker>>,
+    mut q_preview: Query<(Entity, &mut Visibility, Option<&Children>), With<LightPreviewMarker>>,
+    mut q_path: Query<
+        (
+            &mut Mesh2d,
+            &mut MeshMaterial2d<ColorMaterial>,
+            &mut Transform,
+        ),
+        (With<LightPreviewPathMarker>, Without<LightPreviewMarker>),
+    >,
+    mut q_end: Query<
+        (
+            &mut MeshMaterial2d<ColorMaterial>,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        (
+            With<LightPreviewEndMarker>,
+            Without<LightPreviewPathMarker>,
+            Without<LightPreviewMarker>,
+        ),
+    >,
+    mut q_sensor_markers: Query<
+        (&Children, &mut Transform, &mut Visibility),
+        (
+            With<LightPreviewSensorMarker>,
+            Without<LightPreviewEndMarker>,
+            Without<LightPreviewPathMarker>,
+            Without<LightPreviewMarker>,
+        ),
+    >,
+    mut q_text: Query<&mut Text2d>,
+    mut assets: LightPreviewAssets,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let Ok((preview, mut preview_visibility, children)) = q_preview.get_single_mut() else {
+        return;
+    };
+    let Ok((transform, inventory)) = q_player.get_single() else {
+        return;
+    };
+    let Ok(cursor_pos) = q_cursor.get_single() else {
+        return;
+    };
+    let Ok(light_segment_z) = q_light_segment_z.get_single() else {
+        return;
+    };
+    if !inventory.can_shoot() {
+        *preview_visibility = Visibility::Hidden;
+        return;
+    }
+
+    let shoot_color = inventory.current_color.unwrap();
+
+    let ray_pos = transform.translation().truncate();
+    let ray_dir = aim.direction(ray_pos, cursor_pos.pos, shoot_color);
+
+    let dummy_source = LightBeamSource {
+        start_pos: ray_pos,
+        start_dir: ray_dir,
+        time_traveled: 10000.0, // LOL
+        color: shoot_color,
+    };
+    let Some(playback) = aim.trace(&dummy_source) else {
+        return;
+    };
+
+    let Some(mesh) = light_beam_mesh(&playback.iter_points(&dummy_source).collect::<Vec<_>>())
+    else {
+        *preview_visibility = Visibility::Hidden;
+        return;
+    };
+    *preview_visibility = Visibility::Visible;
+    let z = light_segment_z.translation().z;
+
+    if let Ok((mut mesh_2d, mut material, mut path_transform)) = q_path.get_single_mut() {
+        match assets.meshes.get_mut(&mesh_2d.0) {
+            Some(old_mesh) => *old_mesh = mesh,
+            None => mesh_2d.0 = assets.meshes.add(mesh),
+        }
+        *material = assets.data.path_material_map[shoot_color].clone();
+        path_transform.translation.z = z;
+    }
+
+    if let Ok((mut material, mut end_transform, mut end_visibility)) = q_end.get_single_mut() {
+        let blocked = playback
+            .intersections
+            .last()
+            .filter(|intersection| q_black_ray.contains(intersection.entity))
+            .map(|intersection| intersection.point);
+        let end = match (blocked, playback.end_point) {
+            (Some(point), _) => Some((point, &assets.data.blocked_material)),
+            (None, Some(point)) => Some((point, &assets.data.out_of_range_material)),
+            (None, None) => None,
+        };
+        match end {
+            Some((point, end_material)) => {
+                *material = end_material.clone();
+                end_transform.translation = point.extend(z);
+                *end_visibility = Visibility::Inherited;
+            }
+            None => *end_visibility = Visibility::Hidden,
+        }
+    }
+
+    let hits = preview_sensor_hits(
+        &playback,
+        &dummy_source,
+        &q_sensors,
+        fixed_time.timestep().as_secs_f32(),
+    );
+    let markers: Vec<Entity> = children
+        .map(|children| {
+            children
+                .iter()
+                .copied()
+                .filter(|child| q_sensor_markers.contains(*child))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut markers = markers.into_iter();
+    for hit in hits.iter() {
+        let label = format!("{:.1}s", hit.activation_secs);
+        let pos = hit.pos.extend(z);
+        match markers.next() {
+            Some(marker) => {
+                let (marker_children, mut marker_transform, mut marker_visibility) =
+                    q_sensor_markers.get_mut(marker).unwrap();
+                marker_transform.translation = pos;
+                *marker_visibility = Visibility::Inherited;
+                for child in marker_children.iter() {
+                    if let Ok(mut text) = q_text.get_mut(*child) {
+                        text.0.clone_from(&label);
+                    }
+                }
+            }
+            None => {
+                commands.entity(preview).with_children(|preview| {
+                    preview
+                        .spawn((
+                            LightPreviewSensorMarker,
+                            assets.data.sensor_mesh.clone(),
+                            assets.data.sensor_material.clone(),
+                            Transform::from_translation(pos),
+                            Visibility::Inherited,
+                            HIGHRES_LAYER,
+                        ))
+                        .with_child((
+                            Text2d::new(label),
+                            assets.data.font.clone(),
+                            Transform::from_xyz(0.0, SENSOR_MARKER_RADIUS + 6.0, 0.0),
+                            HIGHRES_LAYER,
+                        ));
+                });
+            }
+        }
+    }
+    for marker in markers {
+        let (_, _, mut marker_visibility) = q_sensor_markers.get_mut(marker).unwrap();
+        *marker_visibility = Visibility::Hidden;
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    config::Config,
+    input::{update_cursor_world_coords, CursorWorldCoords},
+    level::{sensor::LightSensor, CurrentLevel, LevelSystems},
+    light::{
+        segments::{
+            despawn_light_beam, simulate_light_sources, LightBeamPoints, LightSegmentCache,
+            PrevLightBeamPlayback, RecallingBeam,
+        },
+        LightBeamSource,
+    },
+    player::{not_input_locked, PlayerMarker},
+};
+
+use super::{should_shoot_light, PlayerBeam, PlayerLightInventory};
+
+/// How close to the origin of a beam the cursor has to be to recall it with a right click.
+const RECALL_CLICK_RADIUS: f32 = 8.0;
+
+/// How much shorter a beam gets every [`FixedUpdate`] while it is recalled.
+const RECALL_SPEED: f32 = 16.0;
+
+/// [`Plugin`] that lets the player take back a beam they have shot, returning its color to the
+/// [`PlayerLightInventory`].
+pub struct BeamRecallPlugin;
+
+impl Plugin for BeamRecallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                recall_current_color.run_if(recall_key_just_pressed),
+                recall_clicked_beam.run_if(input_just_pressed(MouseButton::Right)),
+            )
+                .run_if(not_input_locked)
+                .run_if(recall_allowed)
+                .in_set(LevelSystems::Simulation)
+                .after(update_cursor_world_coords)
+                .before(should_shoot_light::<false>),
+        )
+        .add_systems(
+            FixedUpdate,
+            retract_recalling_beams
+                .before(simulate_light_sources)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+fn recall_key_just_pressed(keys: Res<ButtonInput<KeyCode>>, config: Res<Config>) -> bool {
+    keys.just_pressed(config.controls_config.key_recall)
+}
+
+fn recall_allowed(current_level: Res<CurrentLevel>) -> bool {
+    current_level.allow_recall
+}
+
+/// Starts retracting a beam. The beam can be longer than the path it draws if it hit nothing for
+/// a while, so its travel time is clamped first to make the retraction start right away.
+fn start_recall(
+    commands: &mut Commands,
+    entity: Entity,
+    source: &mut LightBeamSource,
+    points: Option<&LightBeamPoints>,
+) {
+    if let Some(points) = points {
+        source.time_traveled = source.time_traveled.min(points.length());
+    }
+    commands.entity(entity).insert(RecallingBeam);
+}
+
+/// [`System`] that recalls the beam of the currently selected color when the recall key is
+/// pressed.
+#[allow(clippy::type_complexity)]
+pub fn recall_current_color(
+    mut commands: Commands,
+    q_player: Query<&PlayerLightInventory, With<PlayerMarker>>,
+    mut q_beams: Query<
+        (Entity, &mut LightBeamSource, Option<&LightBeamPoints>),
+        (With<PlayerBeam>, Without<RecallingBeam>),
+    >,
+) {
+    let Ok(inventory) = q_player.get_single() else {
+        return;
+    };
+    let Some(current_color) = inventory.current_color else {
+        return;
+    };
+
+    for (entity, mut source, points) in q_beams.iter_mut() {
+        if source.color == current_color {
+            start_recall(&mut commands, entity, &mut source, points);
+        }
+    }
+}
+
+/// [`System`] that recalls the beam whose origin compass was right clicked. Right click also
+/// cancels an aim, so nothing is recalled while the player is aiming.
+#[allow(clippy::type_complexity)]
+pub fn recall_clicked_beam(
+    mut commands: Commands,
+    q_player: Query<&PlayerLightInventory, With<PlayerMarker>>,
+    q_cursor: Query<&CursorWorldCoords>,
+    mut q_beams: Query<
+        (Entity, &mut LightBeamSource, Option<&LightBeamPoints>),
+        (With<PlayerBeam>, Without<RecallingBeam>),
+    >,
+) {
+    if q_player
+        .get_single()
+        .is_ok_and(|inventory| inventory.should_shoot)
+    {
+        return;
+    }
+    let Ok(cursor_pos) = q_cursor.get_single() else {
+        return;
+    };
+
+    let closest = q_beams
+        .iter_mut()
+        .map(|beam| (beam.1.start_pos.distance(cursor_pos.pos), beam))
+        .filter(|(distance, _)| *distance <= RECALL_CLICK_RADIUS)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    if let Some((_, (entity, mut source, points))) = closest {
+        start_recall(&mut commands, entity, &mut source, points);
+    }
+}
+
+/// [`System`] that shortens each recalled beam back along its path. Sensors past the new end of
+/// the beam are un-hit by [`simulate_light_sources`]; once the beam is fully retracted it is
+/// despawned and its color returns to the [`PlayerLightInventory`].
+pub fn retract_recalling_beams(
+    mut commands: Commands,
+    mut q_beams: Query<(Entity, &mut LightBeamSource, &PrevLightBeamPlayback), With<RecallingBeam>>,
+    mut q_player: Query<&mut PlayerLightInventory, With<PlayerMarker>>,
+    mut q_light_sensor: Query<&mut LightSensor>,
+    mut segment_cache: ResMut<LightSegmentCache>,
+) {
+    for (entity, mut source, prev_playback) in q_beams.iter_mut() {
+        source.time_traveled -= RECALL_SPEED;
+        if source.time_traveled > 0.0 {
+            continue;
+        }
+
+        despawn_light_beam(
+            &mut commands,
+            entity,
+            &source,
+            prev_playback,
+            &mut q_light_sensor,
+            &mut segment_cache,
+        );
+        if let Ok(mut inventory) = q_player.get_single_mut() {
+            inventory.sources[source.color] += 1;
+        }
+    }
+}
@@ -26,6 +26,8 @@ impl Plugin for LightUiPlugin {
 #[derive(Resource)]
 pub struct LightUiIcons {
     icon_entities: EnumMap<LightColor, Entity>,
+    /// Text showing how many beams of each color are left
+    count_entities: EnumMap<LightColor, Entity>,
 }
 
 pub fn spawn_light_icons(
@@ -85,6 +87,7 @@ pub fn spawn_light_icons(
 
     let mut spawn_and_get_icon_id = |val: LightColor| {
         let mut icon: Option<Entity> = None;
+        let mut count: Option<Entity> = None;
         let text = match val {
             LightColor::Green => "1",
             LightColor::Purple => "2",
@@ -120,17 +123,35 @@ pub fn spawn_light_icons(
                             Text::new(text),
                             font.clone().with_font_size(24.),
                         ))
+                        .with_children(|icon| {
+                            count = Some(
+                                icon.spawn((
+                                    Node {
+                                        position_type: PositionType::Absolute,
+                                        right: Val::Percent(0.),
+                                        bottom: Val::Percent(0.),
+                                        ..default()
+                                    },
+                                    Text::new(""),
+                                    font.clone().with_font_size(18.),
+                                ))
+                                .id(),
+                            );
+                        })
                         .id(),
                 );
             });
-        icon.unwrap()
+        (icon.unwrap(), count.unwrap())
     };
 
-    let icon_entities = enum_map! {
+    let entities = enum_map! {
         val => spawn_and_get_icon_id(val),
     };
 
-    commands.insert_resource(LightUiIcons { icon_entities });
+    commands.insert_resource(LightUiIcons {
+        icon_entities: enum_map! { val => entities[val].0 },
+        count_entities: enum_map! { val => entities[val].1 },
+    });
 }
 
 #[derive(Component)]
@@ -141,6 +162,7 @@ pub fn update_light_icons(
     current_level: Res<CurrentLevel>,
     q_player: Query<&PlayerLightInventory, With<PlayerMarker>>,
     mut q_nodes: Query<(&mut Node, &mut ImageNode)>,
+    mut q_count_text: Query<&mut Text>,
 ) {
     let Ok(player_light_inventory) = q_player.get_single() else {
         return;
@@ -153,11 +175,20 @@ pub fn update_light_icons(
 
         if *can_use {
             icon_node.display = Display::Block;
-            if player_light_inventory.sources[color] {
+            let remaining = player_light_inventory.sources[color];
+            if remaining > 0 {
                 icon_image.color.set_alpha(1.);
             } else {
                 icon_image.color.set_alpha(0.2);
             }
+            // only show the count in levels that give more than one beam of a color
+            if let Ok(mut count_text) = q_count_text.get_mut(light_icons.count_entities[color]) {
+                count_text.0 = if current_level.beam_counts[color] > 1 {
+                    remaining.to_string()
+                } else {
+                    String::new()
+                };
+            }
         } else {
             icon_node.display = Display::None;
         }
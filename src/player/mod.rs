@@ -7,12 +7,14 @@ use strand::PlayerStrandPlugin;
 
 use crate::{animation::AnimationConfig, level::LevelSystems};
 
+use grapple::{LightGrapple, PlayerGrapplePlugin};
 use kill::PlayerKillPlugin;
 use light::{PlayerLightInventory, PlayerLightPlugin};
 use movement::{PlayerMovement, PlayerMovementPlugin};
 use spawn::{init_player_bundle, update_player_entity};
 
-mod animation;
+pub mod animation;
+pub mod grapple;
 pub mod kill;
 pub mod light;
 pub mod match_player;
@@ -29,6 +31,7 @@ impl Plugin for PlayerManagementPlugin {
             .add_plugins(PlayerMovementPlugin)
             .add_plugins(PlayerKillPlugin)
             .add_plugins(PlayerStrandPlugin)
+            .add_plugins(PlayerGrapplePlugin)
             .add_systems(
                 PreUpdate,
                 update_player_entity.in_set(LevelSystems::Processing),
@@ -75,6 +78,7 @@ pub struct PlayerBundle {
     restitution: Restitution,
     player_movement: PlayerMovement,
     light_inventory: PlayerLightInventory,
+    grapple: LightGrapple,
     // point_lighting: LineLight2d,
     animation_config: AnimationConfig,
     animation_type: PlayerAnimationType,
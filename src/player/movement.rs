@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::config::Config;
+use crate::input::{HeldAction, HeldActions};
 use crate::level::LevelSystems;
 
 use super::{not_input_locked, InputLocked, PlayerMarker};
@@ -23,6 +24,8 @@ const PLAYER_JUMP_VEL: f32 = 2.2;
 const PLAYER_MOVE_VEL: f32 = 0.6;
 /// The y velocity subtracted from the player due to gravity.
 const PLAYER_GRAVITY: f32 = 0.15;
+/// Distance from the player's center to their feet.
+const PLAYER_HALF_HEIGHT: f32 = 9.0;
 
 pub struct PlayerMovementPlugin;
 
@@ -53,7 +56,7 @@ impl Plugin for PlayerMovementPlugin {
 }
 
 /// [`Component`] that stores information about the player's movement state.
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone)]
 pub struct PlayerMovement {
     /// Holds information that is passed into the rapier character controller's translation
     pub velocity: Vec2,
@@ -64,6 +67,18 @@ pub struct PlayerMovement {
     jump_boost_ticks_remaining: isize,
 }
 
+impl PlayerMovement {
+    /// How far above a one-way platform the player's center has to be for the platform to carry
+    /// them. Crouching lowers it so that the player can drop through.
+    pub fn one_way_cutoff(&self) -> f32 {
+        if self.crouching {
+            PLAYER_HALF_HEIGHT / 2.0
+        } else {
+            PLAYER_HALF_HEIGHT
+        }
+    }
+}
+
 /// [`System`] that is run the frame the space bar is pressed. Allows the player to jump for the
 /// next couple of frames.
 pub fn queue_jump(mut q_player: Query<&mut PlayerMovement, With<PlayerMarker>>) {
@@ -108,6 +123,7 @@ pub fn move_player(
         With<PlayerMarker>,
     >,
     keys: Res<ButtonInput<KeyCode>>,
+    held_actions: Res<HeldActions>,
     config: Res<Config>,
 ) {
     let Ok((mut controller, output, mut player, movement_locked)) = q_player.get_single_mut()
@@ -163,7 +179,7 @@ pub fn move_player(
         moved = true;
     }
 
-    player.sneaking = keys.pressed(KeyCode::ControlLeft);
+    player.sneaking = held_actions.active(HeldAction::Sneak);
     let temp_max_h_vel = if player.sneaking {
         PLAYER_MAX_H_VEL / 2.
     } else {
@@ -8,6 +8,7 @@ use crate::{
 
 use super::{
     animation::{PlayerAnimationType, ANIMATION_FRAMES},
+    grapple::LightGrapple,
     light::PlayerLightInventory,
     movement::PlayerMovement,
     PlayerBundle, PlayerHurtMarker, PlayerMarker,
@@ -45,6 +46,7 @@ pub fn init_player_bundle(_: &EntityInstance) -> PlayerBundle {
             combine_rule: CoefficientCombineRule::Min,
         },
         light_inventory: PlayerLightInventory::default(),
+        grapple: LightGrapple::default(),
         animation_type: PlayerAnimationType::Idle,
         animation_config: AnimationConfig::from(PlayerAnimationType::Idle),
     }
@@ -99,6 +101,7 @@ pub fn update_player_entity(
                 GroupLabel::HURT_BOX
                     | GroupLabel::TERRAIN
                     | GroupLabel::CRYSTAL_SHARD
+                    | GroupLabel::CHECKPOINT
                     | GroupLabel::PLATFORM,
             ))
             .insert(LineLight2d::point(
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use bevy::{ecs::schedule::SystemConfigs, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::ui::localization::LocalizedText;
+
+/// How long the notice about a failed save stays on screen.
+const SAVE_FAILED_NOTICE_DURATION: Duration = Duration::from_secs(6);
+
+/// [`Plugin`] that tells the player when their progress couldn't be saved. The data itself is
+/// read with [`load`] and written with [`store`] or [`persist_resource`] by whatever owns it.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveFailedEvent>().add_systems(
+            Update,
+            (
+                show_save_failed_notice.run_if(on_event::<SaveFailedEvent>),
+                hide_save_failed_notice,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// [`Event`] sent when [`store`] fails, which shows a notice to the player once per session.
+#[derive(Event)]
+pub struct SaveFailedEvent;
+
+#[derive(Component)]
+pub struct SaveFailedNotice(Timer);
+
+/// Reads the player data saved as `name`, or the default if nothing was saved or it can't be
+/// read.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(contents) = platform::read(name) else {
+        return T::default();
+    };
+    toml::from_str(&contents).unwrap_or_else(|err| {
+        warn!("Failed to parse saved {name}, starting over: {err}");
+        T::default()
+    })
+}
+
+/// Saves `data` as `name`, logging why it failed if it did.
+pub fn store<T: Serialize>(name: &str, data: &T) -> Result<(), String> {
+    let saved = toml::to_string(data)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| platform::write(name, &contents));
+    if let Err(err) = &saved {
+        warn!("Failed to save {name}: {err}");
+    }
+    saved
+}
+
+/// Returns a [`System`] that saves the [`Resource`] `T` as `name` whenever it changes, except
+/// when it was just inserted (usually from [`load`]). Sends a [`SaveFailedEvent`] if it fails.
+pub fn persist_resource<T: Resource + Serialize>(name: &'static str) -> SystemConfigs {
+    (move |data: Res<T>, mut ev_save_failed: EventWriter<SaveFailedEvent>| {
+        if data.is_added() {
+            return;
+        }
+        if store(name, &*data).is_err() {
+            ev_save_failed.send(SaveFailedEvent);
+        }
+    })
+    .run_if(resource_changed::<T>)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::path::PathBuf;
+
+    /// The directory the game saves to in the user's data directory, so that it doesn't depend
+    /// on where the game is run from.
+    fn save_dir() -> Option<PathBuf> {
+        let var = |name| std::env::var_os(name).map(PathBuf::from);
+        let data_dir = if cfg!(target_os = "windows") {
+            var("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library/Application Support"))
+        } else {
+            var("XDG_DATA_HOME").or_else(|| var("HOME").map(|home| home.join(".local/share")))
+        };
+        data_dir.map(|dir| dir.join("Lightborne"))
+    }
+
+    pub fn read(name: &str) -> Option<String> {
+        std::fs::read_to_string(save_dir()?.join(format!("{name}.toml"))).ok()
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), String> {
+        let dir = save_dir().ok_or("no data directory")?;
+        std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        std::fs::write(dir.join(format!("{name}.toml")), contents).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    pub fn read(_name: &str) -> Option<String> {
+        None
+    }
+
+    pub fn write(_name: &str, _contents: &str) -> Result<(), String> {
+        Err("saving isn't supported in the browser".to_string())
+    }
+}
+
+/// [`System`] that shows a notice the first time a save fails.
+pub fn show_save_failed_notice(
+    mut commands: Commands,
+    mut ev_save_failed: EventReader<SaveFailedEvent>,
+    mut shown: Local<bool>,
+    asset_server: Res<AssetServer>,
+) {
+    ev_save_failed.clear();
+    if *shown {
+        return;
+    }
+    *shown = true;
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(16.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            SaveFailedNotice(Timer::new(SAVE_FAILED_NOTICE_DURATION, TimerMode::Once)),
+        ))
+        .with_child((
+            LocalizedText::new("save.failed"),
+            TextFont {
+                font: asset_server.load("fonts/Outfit-Medium.ttf"),
+                font_size: 20.,
+                ..default()
+            },
+        ));
+}
+
+pub fn hide_save_failed_notice(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_notice: Query<(Entity, &mut SaveFailedNotice)>,
+) {
+    for (entity, mut notice) in q_notice.iter_mut() {
+        if notice.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -19,6 +19,7 @@ impl GroupLabel {
     pub const CRYSTAL_SHARD: Group = Group::GROUP_10;
     pub const PLATFORM: Group = Group::GROUP_11;
     pub const BLACK_RAY: Group = Group::GROUP_12;
+    pub const CHECKPOINT: Group = Group::GROUP_13;
     pub const ALL: Group = Group::from_bits_truncate(!0);
 }
 
@@ -26,6 +27,7 @@ impl GroupLabel {
 pub enum GameState {
     Playing,
     Animating,
+    Cutscene,
     Paused,
     Ui,
 }
@@ -36,10 +38,6 @@ pub enum AnimationState {
     #[default]
     Switch,
     Respawn,
-    Shard,
-    ShardDialogue, // FIXME: copied to shit LOL
-    Cruciera,
-    CrucieraDialogue,
 }
 
 #[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
@@ -39,7 +39,7 @@ impl FromWorld for BgmTracks {
     }
 }
 
-#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum BgmTrack {
     MustntStop,
     LightInTheDark,
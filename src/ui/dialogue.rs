@@ -0,0 +1,523 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemId, prelude::*};
+use serde::Deserialize;
+
+use super::localization::Localization;
+
+/// The [`DialogueTable`], embedded at compile time so it is also there on the web build.
+const DIALOGUE_TABLE: &str = include_str!("../../assets/dialogue.toml");
+
+/// Mood used for lines that don't set one, or whose speaker doesn't have a portrait for it.
+const DEFAULT_MOOD: &str = "neutral";
+
+/// Number keys used to pick the choices of a line, in order.
+const CHOICE_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// [`Plugin`] that loads the [`DialogueTable`] and plays the dialogues started with
+/// [`StartDialogueEvent`].
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        let table: DialogueTable =
+            toml::from_str(DIALOGUE_TABLE).expect("Failed to parse dialogue.toml");
+        app.insert_resource(table)
+            .add_event::<StartDialogueEvent>()
+            .add_systems(Startup, report_untranslated_dialogue)
+            .add_systems(
+                Update,
+                (
+                    start_dialogue.run_if(on_event::<StartDialogueEvent>),
+                    (advance_dialogue, update_dialogue_box)
+                        .chain()
+                        .run_if(resource_exists::<ActiveDialogue>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// [`Resource`] holding every dialogue and the portraits of their speakers, read from
+/// `assets/dialogue.toml`.
+#[derive(Deserialize, Resource, Default, Debug)]
+#[serde(default)]
+pub struct DialogueTable {
+    /// Dialogue box image of each mood of each speaker
+    speakers: HashMap<String, HashMap<String, String>>,
+    dialogues: HashMap<String, Dialogue>,
+}
+
+impl DialogueTable {
+    /// The dialogue box image to show for `line`.
+    fn portrait(&self, line: &DialogueLine) -> Option<&str> {
+        let moods = self.speakers.get(&line.speaker)?;
+        moods
+            .get(line.mood.as_deref().unwrap_or(DEFAULT_MOOD))
+            .or_else(|| moods.get(DEFAULT_MOOD))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Dialogue {
+    /// How fast the text of each line is typed out
+    #[serde(default = "default_chars_per_second")]
+    chars_per_second: f32,
+    lines: Vec<DialogueLine>,
+}
+
+fn default_chars_per_second() -> f32 {
+    50.0
+}
+
+impl Dialogue {
+    /// The index of the line shown after the line at `current`, or [`None`] if the dialogue ends
+    /// there. Follows `goto` instead of moving to the next line when it is set.
+    fn next_line(&self, current: usize, goto: Option<&str>) -> Option<usize> {
+        if self.lines[current].end {
+            return None;
+        }
+        let Some(label) = goto else {
+            return (current + 1 < self.lines.len()).then_some(current + 1);
+        };
+        let next = self
+            .lines
+            .iter()
+            .position(|line| line.label.as_deref() == Some(label));
+        if next.is_none() {
+            warn!("Dialogue label {label} doesn't exist, ending the dialogue");
+        }
+        next
+    }
+
+    /// How many characters of `text`, the text of the line at `index`, are shown after it has
+    /// been on screen for `elapsed` seconds.
+    fn shown_chars(&self, index: usize, text: &str, elapsed: f32) -> usize {
+        let pause = self.lines[index].pause;
+        let typed = ((elapsed - pause).max(0.0) * self.chars_per_second) as usize;
+        typed.min(text.chars().count())
+    }
+}
+
+/// The text of `line`, the line at `index` of the dialogue `id`, in the selected language.
+fn line_text<'a>(
+    localization: &'a Localization,
+    id: &str,
+    index: usize,
+    line: &'a DialogueLine,
+) -> &'a str {
+    localization
+        .dialogue(&format!("{id}.{index}"))
+        .unwrap_or(&line.text)
+}
+
+/// The text of the choice at `choice` of `line`, the line at `index` of the dialogue `id`, in the
+/// selected language.
+fn choice_text<'a>(
+    localization: &'a Localization,
+    id: &str,
+    index: usize,
+    line: &'a DialogueLine,
+    choice: usize,
+) -> &'a str {
+    localization
+        .dialogue(&format!("{id}.{index}.{choice}"))
+        .unwrap_or(&line.choices[choice].text)
+}
+
+#[derive(Deserialize, Debug)]
+struct DialogueLine {
+    speaker: String,
+    mood: Option<String>,
+    text: String,
+    /// Seconds to wait before the text starts being typed out
+    #[serde(default)]
+    pause: f32,
+    label: Option<String>,
+    goto: Option<String>,
+    #[serde(default)]
+    end: bool,
+    #[serde(default)]
+    choices: Vec<DialogueChoice>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DialogueChoice {
+    text: String,
+    goto: String,
+}
+
+/// [`Event`] that opens the dialogue box and plays the dialogue with the id `id`. `on_end` is run
+/// once the dialogue box is closed.
+#[derive(Event)]
+pub struct StartDialogueEvent {
+    pub id: String,
+    pub on_end: SystemId,
+}
+
+/// [`Resource`] that exists while a dialogue is being played.
+#[derive(Resource)]
+pub struct ActiveDialogue {
+    id: String,
+    line: usize,
+    /// Seconds the current line has been on screen for
+    elapsed: f32,
+    /// Whether the rest of the current line was skipped to
+    revealed: bool,
+    on_end: SystemId,
+}
+
+#[derive(Component)]
+pub struct DialogueBoxMarker;
+
+#[derive(Component)]
+pub struct DialogueTextMarker;
+
+#[derive(Component)]
+pub struct DialogueImageMarker;
+
+#[derive(Component)]
+pub struct DialogueChoicesMarker;
+
+/// [`Component`] on the button for one of the choices of the line at `line`.
+#[derive(Component)]
+pub struct DialogueChoiceButton {
+    line: usize,
+    choice: usize,
+}
+
+/// [`System`] that opens the dialogue box when a [`StartDialogueEvent`] is received. Dialogues
+/// that don't exist end right away.
+pub fn start_dialogue(
+    mut commands: Commands,
+    mut ev_start_dialogue: EventReader<StartDialogueEvent>,
+    table: Res<DialogueTable>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(StartDialogueEvent { id, on_end }) = ev_start_dialogue.read().last() else {
+        return;
+    };
+    let Some(first_line) = table
+        .dialogues
+        .get(id)
+        .and_then(|dialogue| dialogue.lines.first())
+    else {
+        error!("Dialogue {id} doesn't exist or has no lines");
+        commands.run_system(*on_end);
+        return;
+    };
+
+    let font = TextFont {
+        font: asset_server.load("fonts/Outfit-Medium.ttf"),
+        ..default()
+    };
+    let portrait = table
+        .portrait(first_line)
+        .map(|portrait| asset_server.load(portrait))
+        .unwrap_or_default();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(32.)),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            DialogueBoxMarker,
+        ))
+        .with_children(|container| {
+            container
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.),
+                        max_width: Val::Px(1280.),
+                        height: Val::Auto,
+                        aspect_ratio: Some(2775. / 630.), // FIXME: magic values!
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        padding: UiRect::new(
+                            Val::Px(200.),
+                            Val::Px(200.),
+                            Val::Px(32.),
+                            Val::Px(32.),
+                        ),
+                        ..default()
+                    },
+                    DialogueImageMarker,
+                    ImageNode::new(portrait),
+                ))
+                .with_children(|text_box| {
+                    text_box.spawn((
+                        Node::default(),
+                        font.clone().with_font_size(24.),
+                        TextLayout::new_with_justify(JustifyText::Center),
+                        Text::new(""),
+                        DialogueTextMarker,
+                    ));
+                });
+            container.spawn((
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                DialogueChoicesMarker,
+            ));
+        });
+
+    commands.insert_resource(ActiveDialogue {
+        id: id.clone(),
+        line: 0,
+        elapsed: 0.0,
+        revealed: false,
+        on_end: *on_end,
+    });
+}
+
+/// [`System`] that moves the [`ActiveDialogue`] along. Space, Enter or a click shows the rest of
+/// the line being typed out, and then moves on to the next line, unless the line has choices to
+/// pick from.
+#[allow(clippy::too_many_arguments)]
+pub fn advance_dialogue(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
+    table: Res<DialogueTable>,
+    localization: Res<Localization>,
+    mut dialogue: ResMut<ActiveDialogue>,
+    q_choice_buttons: Query<(&Interaction, &DialogueChoiceButton), Changed<Interaction>>,
+    q_dialogue_box: Query<Entity, With<DialogueBoxMarker>>,
+) {
+    let script = &table.dialogues[&dialogue.id];
+    let line = &script.lines[dialogue.line];
+    let text = line_text(&localization, &dialogue.id, dialogue.line, line);
+    dialogue.elapsed += time.delta_secs();
+
+    let typed = dialogue.revealed
+        || script.shown_chars(dialogue.line, text, dialogue.elapsed) == text.chars().count();
+    let confirmed = keys.any_just_pressed([KeyCode::Space, KeyCode::Enter])
+        || mouse.just_pressed(MouseButton::Left);
+
+    if !typed {
+        dialogue.revealed |= confirmed;
+        return;
+    }
+
+    let next = if line.choices.is_empty() {
+        if !confirmed {
+            return;
+        }
+        script.next_line(dialogue.line, line.goto.as_deref())
+    } else {
+        let clicked = q_choice_buttons
+            .iter()
+            .find(|(interaction, button)| {
+                **interaction == Interaction::Pressed && button.line == dialogue.line
+            })
+            .map(|(_, button)| button.choice);
+        let pressed = CHOICE_KEYS
+            .iter()
+            .take(line.choices.len())
+            .position(|key| keys.just_pressed(*key));
+        let Some(choice) = clicked.or(pressed) else {
+            return;
+        };
+        script.next_line(dialogue.line, Some(&line.choices[choice].goto))
+    };
+
+    match next {
+        Some(next) => {
+            dialogue.line = next;
+            dialogue.elapsed = 0.0;
+            dialogue.revealed = false;
+        }
+        None => {
+            for dialogue_box in q_dialogue_box.iter() {
+                commands.entity(dialogue_box).despawn_recursive();
+            }
+            commands.remove_resource::<ActiveDialogue>();
+            commands.run_system(dialogue.on_end);
+        }
+    }
+}
+
+/// [`System`] that types out the current line of the [`ActiveDialogue`], shows its speaker's
+/// portrait and lists its choices once it has been typed out.
+#[allow(clippy::too_many_arguments)]
+pub fn update_dialogue_box(
+    mut commands: Commands,
+    table: Res<DialogueTable>,
+    localization: Res<Localization>,
+    dialogue: Res<ActiveDialogue>,
+    asset_server: Res<AssetServer>,
+    mut q_dialogue_text: Query<&mut Text, With<DialogueTextMarker>>,
+    mut q_dialogue_image: Query<&mut ImageNode, With<DialogueImageMarker>>,
+    q_choices: Query<Entity, With<DialogueChoicesMarker>>,
+    q_choice_buttons: Query<&DialogueChoiceButton>,
+) {
+    let (Ok(mut text), Ok(mut image), Ok(choices)) = (
+        q_dialogue_text.get_single_mut(),
+        q_dialogue_image.get_single_mut(),
+        q_choices.get_single(),
+    ) else {
+        return;
+    };
+    let script = &table.dialogues[&dialogue.id];
+    let line = &script.lines[dialogue.line];
+
+    let full_text = line_text(&localization, &dialogue.id, dialogue.line, line);
+    let char_count = full_text.chars().count();
+    let shown = match dialogue.revealed {
+        true => char_count,
+        false => script.shown_chars(dialogue.line, full_text, dialogue.elapsed),
+    };
+    if text.chars().count() != shown || localization.is_changed() {
+        *text = full_text.chars().take(shown).collect::<String>().into();
+    }
+
+    if let Some(portrait) = table.portrait(line) {
+        let handle = asset_server.load(portrait);
+        if image.image != handle {
+            image.image = handle;
+        }
+    }
+
+    let show_choices = shown == char_count && !line.choices.is_empty();
+    let showing_choices = q_choice_buttons
+        .iter()
+        .any(|button| button.line == dialogue.line);
+    if show_choices == showing_choices && !localization.is_changed() {
+        return;
+    }
+    commands.entity(choices).despawn_descendants();
+    if !show_choices {
+        return;
+    }
+    let font = TextFont {
+        font: asset_server.load("fonts/Outfit-Medium.ttf"),
+        ..default()
+    };
+    commands.entity(choices).with_children(|parent| {
+        for i in 0..line.choices.len() {
+            let choice = choice_text(&localization, &dialogue.id, dialogue.line, line, i);
+            parent.spawn((
+                Text::new(format!("{}. {choice}", i + 1)),
+                font.clone().with_font_size(24.),
+                Button,
+                DialogueChoiceButton {
+                    line: dialogue.line,
+                    choice: i,
+                },
+            ));
+        }
+    });
+}
+
+/// [`System`] that reports the dialogue lines missing from each language at startup.
+pub fn report_untranslated_dialogue(table: Res<DialogueTable>, localization: Res<Localization>) {
+    let keys: Vec<String> = table
+        .dialogues
+        .iter()
+        .flat_map(|(id, dialogue)| {
+            dialogue
+                .lines
+                .iter()
+                .enumerate()
+                .flat_map(move |(i, line)| {
+                    std::iter::once(format!("{id}.{i}")).chain(
+                        (0..line.choices.len()).map(move |choice| format!("{id}.{i}.{choice}")),
+                    )
+                })
+        })
+        .collect();
+    localization.report_missing_dialogue(keys.iter());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRANCHING: &str = r#"
+        chars_per_second = 10.0
+
+        [[lines]]
+        speaker = "lyra"
+        text = "Hello"
+        pause = 1.0
+        choices = [{ text = "Wave", goto = "wave" }, { text = "Leave", goto = "leave" }]
+
+        [[lines]]
+        label = "wave"
+        speaker = "lyra"
+        text = "*waves*"
+        goto = "leave"
+
+        [[lines]]
+        speaker = "lyra"
+        text = "Skipped"
+        end = true
+
+        [[lines]]
+        label = "leave"
+        speaker = "lyra"
+        text = "Bye"
+    "#;
+
+    #[test]
+    fn embedded_table_has_story_dialogues() {
+        let table: DialogueTable = toml::from_str(DIALOGUE_TABLE).unwrap();
+        for id in [
+            "shard_green",
+            "shard_blue",
+            "shard_white",
+            "shard_purple",
+            "shard_black",
+            "cruciera_intro",
+        ] {
+            assert!(
+                table
+                    .dialogues
+                    .get(id)
+                    .is_some_and(|dialogue| !dialogue.lines.is_empty()),
+                "{id} is missing"
+            );
+        }
+    }
+
+    #[test]
+    fn follows_labels_and_ends() {
+        let dialogue: Dialogue = toml::from_str(BRANCHING).unwrap();
+        assert_eq!(dialogue.next_line(0, Some("wave")), Some(1));
+        assert_eq!(dialogue.next_line(1, Some("leave")), Some(3));
+        assert_eq!(dialogue.next_line(2, None), None);
+        assert_eq!(dialogue.next_line(3, None), None);
+        assert_eq!(dialogue.next_line(0, Some("missing")), None);
+    }
+
+    #[test]
+    fn types_after_pause() {
+        let dialogue: Dialogue = toml::from_str(BRANCHING).unwrap();
+        assert_eq!(dialogue.shown_chars(0, "Hello", 0.5), 0);
+        assert_eq!(dialogue.shown_chars(0, "Hello", 1.25), 2);
+        assert_eq!(dialogue.shown_chars(0, "Hello", 10.0), 5);
+        assert_eq!(dialogue.shown_chars(0, "Salut à toi", 10.0), 11);
+    }
+}
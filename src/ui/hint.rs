@@ -0,0 +1,360 @@
+use std::{collections::HashSet, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, ControlsConfig},
+    level::CurrentLevel,
+    light::LightColor,
+    player::{light::PlayerLightInventory, PlayerMarker},
+    save,
+    shared::{GameState, ResetLevel},
+};
+
+use super::localization::Localization;
+
+/// Name the [`SeenHints`] are saved under.
+const SEEN_HINTS_SAVE: &str = "hints";
+
+/// How long a hint prompt stays on screen if it isn't dismissed.
+const HINT_DURATION: Duration = Duration::from_secs(10);
+
+/// Deaths in the same level after which the quick reset hint is shown.
+const QUICK_RESET_HINT_DEATHS: u32 = 3;
+
+/// Time spent in a level with several colors without switching colors after which the color
+/// switching hint is shown.
+const SWITCH_COLOR_HINT_DELAY: Duration = Duration::from_secs(30);
+
+/// [`Plugin`] that shows the tutorial [`Hint`]s sent with [`ShowHintEvent`] or whose condition is
+/// met, each once.
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(save::load::<SeenHints>(SEEN_HINTS_SAVE))
+            .init_resource::<HintConditions>()
+            .add_event::<ShowHintEvent>()
+            .add_systems(
+                Update,
+                (
+                    track_deaths.run_if(on_event::<ResetLevel>),
+                    check_hint_conditions.run_if(in_state(GameState::Playing)),
+                    show_hint.run_if(on_event::<ShowHintEvent>),
+                    save::persist_resource::<SeenHints>(SEEN_HINTS_SAVE),
+                    update_hint_prompt.run_if(resource_exists::<ActiveHint>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A tutorial hint about one of the controls new players tend to miss.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Hint {
+    SnapAngles,
+    CancelShot,
+    QuickReset,
+    SwitchColor,
+    DropThrough,
+}
+
+impl From<&str> for Hint {
+    fn from(value: &str) -> Self {
+        match value {
+            "snap_angles" => Hint::SnapAngles,
+            "cancel_shot" => Hint::CancelShot,
+            "quick_reset" => Hint::QuickReset,
+            "switch_color" => Hint::SwitchColor,
+            "drop_through" => Hint::DropThrough,
+            _ => panic!("String {} does not represent a Hint", value),
+        }
+    }
+}
+
+impl Hint {
+    /// Key of the hint's text in the string tables, where `{key}` stands for its binding.
+    fn text_key(&self) -> &'static str {
+        match self {
+            Hint::SnapAngles => "hint.snap_angles",
+            Hint::CancelShot => "hint.cancel_shot",
+            Hint::QuickReset => "hint.quick_reset",
+            Hint::SwitchColor => "hint.switch_color",
+            Hint::DropThrough => "hint.drop_through",
+        }
+    }
+
+    /// The input the hint is about, as shown to the player.
+    fn binding(&self, controls: &ControlsConfig, localization: &Localization) -> String {
+        match self {
+            Hint::SnapAngles => key_name(controls.key_snap),
+            Hint::CancelShot => localization.get("controls.right_click").to_string(),
+            Hint::QuickReset => key_name(controls.key_quick_reset),
+            Hint::SwitchColor => color_keys_name(controls),
+            Hint::DropThrough => key_name(controls.key_down),
+        }
+    }
+
+    /// The text of the prompt showing this hint.
+    fn prompt(&self, controls: &ControlsConfig, localization: &Localization) -> String {
+        format!(
+            "{}    [{}] {}",
+            localization
+                .get(self.text_key())
+                .replace("{key}", &self.binding(controls, localization)),
+            key_name(controls.key_dismiss_hint),
+            localization.get("hint.dismiss"),
+        )
+    }
+}
+
+/// The name of a key as printed on the keyboard, e.g. `S` for [`KeyCode::KeyS`].
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// The names of the keys that select each color, e.g. `1/2/3/4/5`.
+pub fn color_keys_name(controls: &ControlsConfig) -> String {
+    controls.key_colors.map(key_name).join("/")
+}
+
+/// [`Event`] that shows a [`Hint`], unless the player has already seen it.
+#[derive(Event)]
+pub struct ShowHintEvent(pub Hint);
+
+/// [`Resource`] holding the hints the player has seen, saved under [`SEEN_HINTS_SAVE`].
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct SeenHints {
+    seen: HashSet<Hint>,
+}
+
+/// [`Resource`] tracking the conditions that show some of the hints, for the current level.
+#[derive(Resource, Default, Debug)]
+pub struct HintConditions {
+    deaths: u32,
+    /// Time spent with the same color selected in a level where Lyra has several
+    same_color_time: Duration,
+    last_color: Option<LightColor>,
+}
+
+/// [`Resource`] that exists while a hint prompt is on screen.
+#[derive(Resource)]
+pub struct ActiveHint {
+    hint: Hint,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct HintPromptMarker;
+
+#[derive(Component)]
+pub struct HintTextMarker;
+
+/// [`System`] that counts Lyra's deaths in the current level.
+pub fn track_deaths(
+    mut ev_reset_level: EventReader<ResetLevel>,
+    mut conditions: ResMut<HintConditions>,
+) {
+    for ev in ev_reset_level.read() {
+        match ev {
+            ResetLevel::Respawn => conditions.deaths += 1,
+            ResetLevel::Switching => *conditions = HintConditions::default(),
+        }
+    }
+}
+
+/// [`System`] that shows the hints whose condition has been met.
+pub fn check_hint_conditions(
+    time: Res<Time>,
+    current_level: Res<CurrentLevel>,
+    q_inventory: Query<&PlayerLightInventory, With<PlayerMarker>>,
+    mut conditions: ResMut<HintConditions>,
+    mut ev_show_hint: EventWriter<ShowHintEvent>,
+) {
+    if conditions.deaths >= QUICK_RESET_HINT_DEATHS {
+        ev_show_hint.send(ShowHintEvent(Hint::QuickReset));
+    }
+
+    let Ok(inventory) = q_inventory.get_single() else {
+        return;
+    };
+    let colors = current_level
+        .allowed_colors
+        .values()
+        .filter(|allowed| **allowed)
+        .count();
+    if colors < 2 || inventory.current_color != conditions.last_color {
+        conditions.last_color = inventory.current_color;
+        conditions.same_color_time = Duration::ZERO;
+        return;
+    }
+    conditions.same_color_time += time.delta();
+    if conditions.same_color_time >= SWITCH_COLOR_HINT_DELAY {
+        ev_show_hint.send(ShowHintEvent(Hint::SwitchColor));
+    }
+}
+
+/// [`System`] that shows the prompt of a [`ShowHintEvent`]'s hint if it hasn't been seen and no
+/// other prompt is on screen.
+pub fn show_hint(
+    mut commands: Commands,
+    mut ev_show_hint: EventReader<ShowHintEvent>,
+    mut seen: ResMut<SeenHints>,
+    active_hint: Option<Res<ActiveHint>>,
+    config: Res<Config>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+) {
+    if active_hint.is_some() {
+        ev_show_hint.clear();
+        return;
+    }
+    let Some(ShowHintEvent(hint)) = ev_show_hint
+        .read()
+        .find(|ShowHintEvent(hint)| !seen.seen.contains(hint))
+    else {
+        return;
+    };
+    seen.seen.insert(*hint);
+
+    let font = TextFont {
+        font: asset_server.load("fonts/Outfit-Medium.ttf"),
+        ..default()
+    };
+    commands.insert_resource(ActiveHint {
+        hint: *hint,
+        timer: Timer::new(HINT_DURATION, TimerMode::Once),
+    });
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(32.)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::FlexEnd,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            HintPromptMarker,
+        ))
+        .with_children(|container| {
+            container
+                .spawn((
+                    Node {
+                        padding: UiRect::axes(Val::Px(16.), Val::Px(8.)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+                ))
+                .with_child((
+                    Text::new(hint.prompt(&config.controls_config, &localization)),
+                    HintTextMarker,
+                    font.with_font_size(24.),
+                ));
+        });
+}
+
+/// [`System`] that hides the hint prompt once it is dismissed or has been on screen for
+/// [`HINT_DURATION`], and keeps it in the selected language.
+#[allow(clippy::too_many_arguments)]
+pub fn update_hint_prompt(
+    mut commands: Commands,
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Res<Config>,
+    localization: Res<Localization>,
+    game_state: Res<State<GameState>>,
+    mut active_hint: ResMut<ActiveHint>,
+    mut q_prompt: Query<(Entity, &mut Visibility), With<HintPromptMarker>>,
+    mut q_text: Query<&mut Text, With<HintTextMarker>>,
+) {
+    let Ok((prompt, mut visibility)) = q_prompt.get_single_mut() else {
+        return;
+    };
+    let playing = *game_state.get() == GameState::Playing;
+    *visibility = if playing {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if localization.is_changed() {
+        if let Ok(mut text) = q_text.get_single_mut() {
+            *text = active_hint
+                .hint
+                .prompt(&config.controls_config, &localization)
+                .into();
+        }
+    }
+    if !playing {
+        return;
+    }
+
+    let dismissed = keys.just_pressed(config.controls_config.key_dismiss_hint);
+    if dismissed || active_hint.timer.tick(time.delta()).finished() {
+        commands.entity(prompt).despawn_recursive();
+        commands.remove_resource::<ActiveHint>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_drop_prefixes() {
+        assert_eq!(key_name(KeyCode::KeyS), "S");
+        assert_eq!(key_name(KeyCode::Digit3), "3");
+        assert_eq!(key_name(KeyCode::Space), "Space");
+    }
+
+    fn show_hint_app(seen: &[Hint]) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .add_event::<ShowHintEvent>()
+            .insert_resource(SeenHints {
+                seen: seen.iter().copied().collect(),
+            })
+            .insert_resource(Config::default())
+            .insert_resource(Localization::load())
+            .add_systems(Update, show_hint);
+        app
+    }
+
+    #[test]
+    fn seen_hints_are_not_shown_again() {
+        let mut app = show_hint_app(&[Hint::QuickReset]);
+        app.world_mut().send_event(ShowHintEvent(Hint::QuickReset));
+        app.update();
+
+        assert!(app.world().get_resource::<ActiveHint>().is_none());
+        let mut q_prompt = app
+            .world_mut()
+            .query_filtered::<(), With<HintPromptMarker>>();
+        assert_eq!(q_prompt.iter(app.world()).count(), 0);
+        assert_eq!(app.world().resource::<SeenHints>().seen.len(), 1);
+    }
+
+    #[test]
+    fn unseen_hints_are_shown_and_marked_seen() {
+        let mut app = show_hint_app(&[Hint::QuickReset]);
+        app.world_mut().send_event(ShowHintEvent(Hint::QuickReset));
+        app.world_mut().send_event(ShowHintEvent(Hint::DropThrough));
+        app.update();
+
+        let active = app.world().resource::<ActiveHint>();
+        assert_eq!(active.hint, Hint::DropThrough);
+        assert!(app
+            .world()
+            .resource::<SeenHints>()
+            .seen
+            .contains(&Hint::DropThrough));
+    }
+}
@@ -16,6 +16,7 @@ use crate::level::{get_ldtk_level_data, level_box_from_level, CurrentLevel};
 use crate::player::PlayerMarker;
 use crate::shared::{GameState, UiState, LYRA_RESPAWN_EPSILON};
 use crate::sound::{BgmTrack, ChangeBgmEvent};
+use crate::ui::localization::LocalizedText;
 use crate::ui::settings::SettingsButton;
 
 pub struct LevelSelectPlugin;
@@ -80,6 +81,8 @@ pub struct LevelSaveData {
     level_index: usize,
     pub complete: bool,
     pub locked: bool,
+    /// Whether the solution of the level was shown, see [`crate::level::solution`]
+    pub hints_used: bool,
 }
 
 impl Ord for LevelSaveData {
@@ -130,6 +133,7 @@ fn init_levels(
             level_index: i,
             complete: config.debug_config.unlock_levels,
             locked: !config.debug_config.unlock_levels,
+            hints_used: false,
         });
     }
     res_levels.0.sort();
@@ -185,7 +189,10 @@ fn spawn_level_select(
             BackgroundColor(Color::BLACK),
         ))
         .with_children(|parent| {
-            parent.spawn((Text::new("Level Select"), font.clone().with_font_size(48.)));
+            parent.spawn((
+                LocalizedText::new("level_select.title"),
+                font.clone().with_font_size(48.),
+            ));
             parent
                 .spawn(Node {
                     width: Val::Percent(100.),
@@ -206,6 +213,7 @@ fn spawn_level_select(
                             level_index: index,
                             complete,
                             locked,
+                            hints_used,
                         },
                     ) in sorted_levels.0.iter().enumerate()
                     {
@@ -218,6 +226,7 @@ fn spawn_level_select(
                                     padding: UiRect::all(Val::Px(8.0)),
                                     margin: UiRect::all(Val::Px(4.0)),
                                     border: UiRect::all(Val::Px(2.0)),
+                                    flex_direction: FlexDirection::Column,
                                     justify_content: JustifyContent::Center,
                                     align_items: AlignItems::Center,
                                     ..default()
@@ -238,7 +247,15 @@ fn spawn_level_select(
                                     Text::new(level_id.to_string())
                                 },
                                 font.clone().with_font_size(24.),
-                            ));
+                            ))
+                            .with_children(|button| {
+                                if *hints_used {
+                                    button.spawn((
+                                        LocalizedText::new("level_select.hints_used"),
+                                        font.clone().with_font_size(12.),
+                                    ));
+                                }
+                            });
                     }
                 });
             parent
@@ -272,7 +289,7 @@ fn spawn_level_select(
                         });
                 });
             parent.spawn((
-                Text::new("Back"),
+                LocalizedText::new("level_select.back"),
                 Button,
                 SettingsButton::Back, // FIXME: reuse settings button
                 font.clone().with_font_size(36.),
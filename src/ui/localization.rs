@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, ui::UiSystem};
+use serde::Deserialize;
+
+/// Code and [`StringTable`] of each language, from `assets/lang/<code>.toml`. The tables are
+/// embedded at compile time so they are also there on the web build, and the fallback language
+/// comes first.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", include_str!("../../assets/lang/en.toml")),
+    ("fr", include_str!("../../assets/lang/fr.toml")),
+];
+
+/// [`Plugin`] that loads the [`StringTable`] of every language and keeps [`LocalizedText`] in the
+/// selected language.
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Localization::load())
+            .add_systems(PostUpdate, update_localized_text.before(UiSystem::Layout));
+    }
+}
+
+/// The strings of one language, read from `assets/lang/<code>.toml`.
+#[derive(Deserialize, Default, Debug)]
+struct StringTable {
+    /// Name of the language shown in the settings, in that language
+    name: String,
+    /// UI strings by key
+    #[serde(default)]
+    strings: HashMap<String, String>,
+    /// Dialogue lines by `<dialogue id>.<line>`, or `<dialogue id>.<line>.<choice>` for choices.
+    /// The fallback language uses the text of `assets/dialogue.toml` instead.
+    #[serde(default)]
+    dialogue: HashMap<String, String>,
+}
+
+/// [`Resource`] holding the [`StringTable`] of every language, with the fallback language first.
+#[derive(Resource, Debug)]
+pub struct Localization {
+    languages: Vec<(String, StringTable)>,
+    /// Index of the selected language in `languages`
+    current: usize,
+}
+
+impl Localization {
+    /// Parses every [`StringTable`] in [`LANGUAGES`] and reports the strings each language is
+    /// missing compared to the fallback language.
+    pub(crate) fn load() -> Self {
+        let languages = LANGUAGES
+            .iter()
+            .map(|(code, contents)| {
+                let table = toml::from_str(contents)
+                    .unwrap_or_else(|err| panic!("Failed to parse lang/{code}.toml: {err}"));
+                (code.to_string(), table)
+            })
+            .collect();
+
+        let localization = Localization {
+            languages,
+            current: 0,
+        };
+        let fallback_keys = &localization.languages[0].1.strings;
+        localization.report_missing("strings", fallback_keys.keys(), |table, key| {
+            table.strings.contains_key(key)
+        });
+        localization
+    }
+
+    /// Logs the keys out of `keys` that each language other than the fallback has no text for.
+    fn report_missing<'a>(
+        &self,
+        kind: &str,
+        keys: impl Iterator<Item = &'a String> + Clone,
+        has_key: impl Fn(&StringTable, &str) -> bool,
+    ) {
+        for (code, table) in self.languages.iter().skip(1) {
+            let mut missing: Vec<&String> =
+                keys.clone().filter(|key| !has_key(table, key)).collect();
+            if missing.is_empty() {
+                continue;
+            }
+            missing.sort();
+            warn!(
+                "Language {code} is missing {} {kind}: {missing:?}",
+                missing.len()
+            );
+        }
+    }
+
+    /// Logs the dialogue lines out of `keys` that each language other than the fallback has no
+    /// translation for.
+    pub fn report_missing_dialogue<'a>(&self, keys: impl Iterator<Item = &'a String> + Clone) {
+        self.report_missing("dialogue lines", keys, |table, key| {
+            table.dialogue.contains_key(key)
+        });
+    }
+
+    /// The text of `key` in the selected language, or in the fallback language if it has none.
+    /// Keys missing from both are shown as is.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        [self.current, 0]
+            .into_iter()
+            .find_map(|i| self.languages[i].1.strings.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// The translation of the dialogue line with `key` in the selected language, if it has one.
+    pub fn dialogue(&self, key: &str) -> Option<&str> {
+        [self.current, 0]
+            .into_iter()
+            .find_map(|i| self.languages[i].1.dialogue.get(key))
+            .map(String::as_str)
+    }
+
+    /// The names of every language, in the order used by [`Localization::set_language`].
+    pub fn language_names(&self) -> Vec<String> {
+        self.languages
+            .iter()
+            .map(|(_, table)| table.name.clone())
+            .collect()
+    }
+
+    pub fn set_language(&mut self, index: usize) {
+        self.current = index.min(self.languages.len() - 1);
+    }
+}
+
+/// [`Component`] for [`Text`] showing the string with this key in the selected language.
+#[derive(Component, Debug, Clone)]
+#[require(Text)]
+pub struct LocalizedText(pub String);
+
+impl LocalizedText {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+}
+
+/// [`System`] that fills in new [`LocalizedText`], and all of it when the language changes.
+pub fn update_localized_text(
+    localization: Res<Localization>,
+    mut q_text: Query<(Ref<LocalizedText>, &mut Text)>,
+) {
+    for (localized, mut text) in q_text.iter_mut() {
+        if localized.is_changed() || localization.is_changed() {
+            *text = localization.get(&localized.0).into();
+        }
+    }
+}
@@ -1,4 +1,7 @@
+pub mod dialogue;
+pub mod hint;
 pub mod level_select;
+pub mod localization;
 pub mod pause;
 pub mod settings;
 pub mod start_menu;
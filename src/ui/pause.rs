@@ -5,7 +5,9 @@ use crate::{
     sound::{BgmTrack, ChangeBgmEvent},
 };
 
-use super::{settings::SettingsButton, start_menu::StartMenuButtonMarker};
+use super::{
+    localization::LocalizedText, settings::SettingsButton, start_menu::StartMenuButtonMarker,
+};
 
 pub struct PausePlugin;
 
@@ -109,7 +111,7 @@ fn spawn_pause(
                 ))
                 .with_children(|parent| {
                     parent.spawn((
-                        Text::new("Paused"),
+                        LocalizedText::new("pause.title"),
                         font.clone().with_font_size(48.),
                         Node {
                             margin: UiRect::all(Val::Px(32.)),
@@ -117,25 +119,25 @@ fn spawn_pause(
                         },
                     ));
                     parent.spawn((
-                        Text::new("Resume"),
+                        LocalizedText::new("pause.resume"),
                         Button,
                         PauseMenuResume,
                         font.clone().with_font_size(36.),
                     ));
                     parent.spawn((
-                        Text::new("Level Select"),
+                        LocalizedText::new("pause.level_select"),
                         Button,
                         StartMenuButtonMarker::Play,
                         font.clone().with_font_size(36.),
                     ));
                     parent.spawn((
-                        Text::new("Settings"),
+                        LocalizedText::new("pause.settings"),
                         Button,
                         StartMenuButtonMarker::Settings,
                         font.clone().with_font_size(36.),
                     ));
                     parent.spawn((
-                        Text::new("Main Menu"),
+                        LocalizedText::new("pause.main_menu"),
                         Button,
                         SettingsButton::Back,
                         font.clone().with_font_size(36.),
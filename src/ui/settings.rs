@@ -5,9 +5,15 @@ use bevy::prelude::*;
 use enum_map::{enum_map, Enum, EnumMap};
 
 use crate::camera::handle_move_camera;
+use crate::config::{Config, ControlsConfig};
+use crate::input::{HeldAction, HeldActions, InputMode};
 use crate::level::speedrun::SpeedrunTimer;
+use crate::level::{rewind::Rewind, solution::SolutionHint};
+use crate::player::light::aim::{AimSettings, AngleSnapping};
 use crate::shared::{GameState, UiState};
 use crate::sound::{BgmTrack, ChangeBgmEvent};
+use crate::ui::hint::{color_keys_name, key_name};
+use crate::ui::localization::{Localization, LocalizedText};
 
 pub struct SettingsPlugin;
 
@@ -43,6 +49,10 @@ pub enum SettingVariant {
     Toggle {
         value: SettingValue<bool>,
     },
+    Choice {
+        value: SettingValue<usize>,
+        options: Vec<String>,
+    },
 }
 
 impl Setting {
@@ -65,6 +75,16 @@ impl Setting {
             },
         }
     }
+
+    fn new_choice(name: String, value: usize, options: Vec<String>) -> Self {
+        Self {
+            name,
+            variant: SettingVariant::Choice {
+                value: SettingValue::from_default(value),
+                options,
+            },
+        }
+    }
 }
 
 #[derive(Component)]
@@ -84,6 +104,9 @@ pub struct SliderButton(f32);
 #[derive(Component, Debug, Clone)]
 pub struct ToggleButton;
 
+#[derive(Component, Debug, Clone)]
+pub struct ChoiceButton;
+
 #[derive(Component)]
 pub struct SettingParentMarker(SettingName);
 
@@ -95,11 +118,36 @@ pub struct UpdateSetting(SettingName);
 
 #[derive(Component, Debug, Clone, PartialEq, Eq, Copy, Enum)]
 pub enum SettingName {
+    Language,
     Volume,
     SpeedrunTimer,
+    AngleSnapping,
+    AimAssist,
+    ShootInput,
+    SnapInput,
+    SneakInput,
+    Rewind,
+    SolutionHints,
 }
 
-fn init_settings() -> Settings {
+/// Options of the [`SettingName::AngleSnapping`] setting, in the order they are cycled through.
+const ANGLE_SNAPPING_OPTIONS: [(&str, AngleSnapping); 5] = [
+    ("settings.angle_snapping.off", AngleSnapping::Off),
+    ("8", AngleSnapping::Increments(8)),
+    ("16", AngleSnapping::Increments(16)),
+    ("32", AngleSnapping::Increments(32)),
+    ("settings.angle_snapping.free", AngleSnapping::Free),
+];
+
+/// Options of the settings that pick the [`InputMode`] of a [`HeldAction`].
+const INPUT_MODE_OPTIONS: [(&str, InputMode); 2] = [
+    ("settings.input_mode.hold", InputMode::Hold),
+    ("settings.input_mode.toggle", InputMode::Toggle),
+];
+
+/// The [`Settings`] with their default values. Names and options are [`LocalizedText`] keys,
+/// except for the language names.
+fn init_settings(localization: &Localization) -> Settings {
     // Settings(vec![Setting::new_slider(
     //     "Volume".to_owned(),
     //     100.0,
@@ -107,29 +155,70 @@ fn init_settings() -> Settings {
     //     "%".to_owned(),
     // )])
     Settings(enum_map! {
+        SettingName::Language => Setting::new_choice(
+            "settings.language".to_owned(),
+            0,
+            localization.language_names(),
+        ),
         SettingName::Volume => Setting::new_slider(
-            "Volume".to_owned(),
+            "settings.volume".to_owned(),
             100.0,
             0.0..=100.0,
             "%".to_owned(),
         ),
         SettingName::SpeedrunTimer => Setting::new_toggle(
-            "Speedrun Timer".to_owned(),
+            "settings.speedrun_timer".to_owned(),
+            false,
+        ),
+        SettingName::AngleSnapping => Setting::new_choice(
+            "settings.angle_snapping".to_owned(),
+            ANGLE_SNAPPING_OPTIONS.len() - 1,
+            ANGLE_SNAPPING_OPTIONS.map(|(name, _)| name.to_owned()).to_vec(),
+        ),
+        SettingName::AimAssist => Setting::new_toggle(
+            "settings.aim_assist".to_owned(),
+            false,
+        ),
+        SettingName::ShootInput => Setting::new_choice(
+            "settings.shoot_input".to_owned(),
+            0,
+            INPUT_MODE_OPTIONS.map(|(name, _)| name.to_owned()).to_vec(),
+        ),
+        SettingName::SnapInput => Setting::new_choice(
+            "settings.snap_input".to_owned(),
+            0,
+            INPUT_MODE_OPTIONS.map(|(name, _)| name.to_owned()).to_vec(),
+        ),
+        SettingName::SneakInput => Setting::new_choice(
+            "settings.sneak_input".to_owned(),
+            0,
+            INPUT_MODE_OPTIONS.map(|(name, _)| name.to_owned()).to_vec(),
+        ),
+        SettingName::Rewind => Setting::new_toggle(
+            "settings.rewind".to_owned(),
             false,
         ),
+        SettingName::SolutionHints => Setting::new_toggle(
+            "settings.solution_hints".to_owned(),
+            true,
+        ),
     })
 }
 
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(init_settings())
+        app.insert_resource(init_settings(app.world().resource::<Localization>()))
             .add_event::<RedrawSetting>()
             .add_event::<UpdateSetting>()
             .add_systems(
                 Update,
                 (
                     spawn_settings.run_if(in_state(UiState::Settings)),
-                    (handle_slider_buttons, handle_toggle_buttons)
+                    (
+                        handle_slider_buttons,
+                        handle_toggle_buttons,
+                        handle_choice_buttons,
+                    )
                         .run_if(in_state(UiState::Settings)),
                     despawn_settings
                         .after(handle_move_camera)
@@ -143,22 +232,66 @@ impl Plugin for SettingsPlugin {
     }
 }
 
-const CONTROLS: [(&str, &str); 8] = [
-    ("Restart", "R"),
-    ("Jump", "Space"),
-    ("Movement", "WASD"),
-    ("Sneak", "Control"),
-    ("Snap Angles", "Shift"),
-    ("Aim Light", "Left Click (Press)"),
-    ("Shoot Light", "Left Click (Release)"),
-    ("Cancel Shoot Light", "Right Click"),
-];
+/// The controls listed in the settings, as the localization key of the action and the binding,
+/// itself a localization key or a key name. Rebindable controls show their [`ControlsConfig`]
+/// binding.
+fn controls(config: &ControlsConfig) -> [(&'static str, String); 17] {
+    [
+        ("controls.restart", key_name(config.key_quick_reset)),
+        ("controls.jump", key_name(config.key_jump)),
+        (
+            "controls.movement",
+            [
+                config.key_up,
+                config.key_left,
+                config.key_down,
+                config.key_right,
+            ]
+            .map(key_name)
+            .concat(),
+        ),
+        ("controls.sneak", "Control".to_string()),
+        ("controls.snap_angles", key_name(config.key_snap)),
+        ("controls.switch_color", color_keys_name(config)),
+        (
+            "controls.aim_light",
+            "controls.left_click_press".to_string(),
+        ),
+        (
+            "controls.shoot_light",
+            "controls.left_click_release".to_string(),
+        ),
+        (
+            "controls.cancel_shoot_light",
+            "controls.right_click".to_string(),
+        ),
+        ("controls.recall_light", key_name(config.key_recall)),
+        ("controls.light_grapple", key_name(config.key_grapple)),
+        (
+            "controls.keyboard_aim",
+            format!(
+                "{} / {}",
+                key_name(config.key_aim_ccw),
+                key_name(config.key_aim_cw)
+            ),
+        ),
+        (
+            "controls.keyboard_shoot_light",
+            key_name(config.key_aim_fire),
+        ),
+        ("controls.interact", key_name(config.key_interact)),
+        ("controls.dismiss_hint", key_name(config.key_dismiss_hint)),
+        ("controls.rewind", key_name(config.key_rewind)),
+        ("controls.solution_hint", key_name(config.key_solution_hint)),
+    ]
+}
 
 fn spawn_settings(
     mut commands: Commands,
     level_select_ui_query: Query<Entity, With<SettingsUiMarker>>,
     asset_server: Res<AssetServer>,
     settings: Res<Settings>,
+    config: Res<Config>,
     mut ev_change_bgm: EventWriter<ChangeBgmEvent>,
 ) {
     if level_select_ui_query.get_single().is_ok() {
@@ -192,7 +325,7 @@ fn spawn_settings(
         })
         .collect::<Vec<_>>();
 
-    let controls_nodes = CONTROLS.map(|(action, control)| {
+    let controls_nodes = controls(&config.controls_config).map(|(action, control)| {
         commands
             .spawn(Node {
                 width: Val::Percent(100.0),
@@ -203,8 +336,14 @@ fn spawn_settings(
                 ..default()
             })
             .with_children(|parent| {
-                parent.spawn((Text::new(action), font.clone().with_font_size(24.0)));
-                parent.spawn((Text::new(control), font.clone().with_font_size(24.0)));
+                parent.spawn((
+                    LocalizedText::new(action),
+                    font.clone().with_font_size(24.0),
+                ));
+                parent.spawn((
+                    LocalizedText::new(control),
+                    font.clone().with_font_size(24.0),
+                ));
             })
             .id()
     });
@@ -226,7 +365,10 @@ fn spawn_settings(
             Interaction::None,
         ))
         .with_children(|parent| {
-            parent.spawn((Text::new("Settings"), font.clone().with_font_size(48.)));
+            parent.spawn((
+                LocalizedText::new("settings.title"),
+                font.clone().with_font_size(48.),
+            ));
             parent
                 .spawn(Node {
                     width: Val::Percent(50.),
@@ -242,12 +384,12 @@ fn spawn_settings(
                         margin: UiRect::vertical(Val::Px(24.)),
                         ..default()
                     },
-                    Text::new("Controls (Fixed)"),
+                    LocalizedText::new("controls.title"),
                     font.clone().with_font_size(36.),
                 ))
                 .add_children(&controls_nodes);
             parent.spawn((
-                Text::new("Back"),
+                LocalizedText::new("settings.back"),
                 Button,
                 SettingsButton::Back,
                 font.clone().with_font_size(36.),
@@ -294,7 +436,10 @@ fn spawn_setting_children(
     font: &TextFont,
 ) {
     let setting = &settings.0[settings_index];
-    parent.spawn((Text::new(&setting.name), font.clone().with_font_size(24.0)));
+    parent.spawn((
+        LocalizedText::new(&setting.name),
+        font.clone().with_font_size(24.0),
+    ));
     parent
         .spawn(Node {
             width: Val::Auto,
@@ -320,7 +465,30 @@ fn spawn_setting_children(
                         ToggleButton,
                     ))
                     .with_child((
-                        Text::new(if value.value { "On" } else { "Off" }),
+                        LocalizedText::new(if value.value {
+                            "settings.on"
+                        } else {
+                            "settings.off"
+                        }),
+                        font.clone().with_font_size(24.0),
+                    ));
+            }
+            SettingVariant::Choice { value, options } => {
+                parent
+                    .spawn((
+                        Node {
+                            width: Val::Px(60.0),
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Row,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        Button,
+                        settings_index,
+                        ChoiceButton,
+                    ))
+                    .with_child((
+                        LocalizedText::new(&options[value.value]),
                         font.clone().with_font_size(24.0),
                     ));
             }
@@ -422,6 +590,42 @@ fn handle_toggle_buttons(
     }
 }
 
+#[allow(clippy::type_complexity)]
+fn handle_choice_buttons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    interaction_query: Query<
+        (&Interaction, &SettingName),
+        (Changed<Interaction>, With<Button>, With<ChoiceButton>),
+    >,
+    mut settings: ResMut<Settings>,
+    mut redraw_ev: EventWriter<RedrawSetting>,
+    mut update_ev: EventWriter<UpdateSetting>,
+) {
+    for (interaction, setting_name) in interaction_query.iter() {
+        if interaction == &Interaction::Pressed {
+            commands.spawn((
+                AudioPlayer::new(asset_server.load("sfx/click.wav")),
+                PlaybackSettings::DESPAWN,
+            ));
+
+            let setting = &mut settings.0[*setting_name];
+            let SettingVariant::Choice {
+                ref mut value,
+                ref options,
+            } = setting.variant
+            else {
+                continue;
+            };
+
+            value.value = (value.value + 1) % options.len();
+
+            redraw_ev.send(RedrawSetting(*setting_name));
+            update_ev.send(UpdateSetting(*setting_name));
+        }
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn handle_slider_buttons(
     mut commands: Commands,
@@ -487,15 +691,27 @@ fn redraw_setting(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_setting(
     mut ev: EventReader<UpdateSetting>,
     settings: Res<Settings>,
     mut global_volume: ResMut<GlobalVolume>,
     mut speedrun_timer: ResMut<SpeedrunTimer>,
+    mut aim_settings: ResMut<AimSettings>,
+    mut held_actions: ResMut<HeldActions>,
+    mut rewind: ResMut<Rewind>,
+    mut solution_hint: ResMut<SolutionHint>,
+    mut localization: ResMut<Localization>,
 ) {
     for UpdateSetting(setting_name) in ev.read() {
         let setting = &settings.0[*setting_name];
         match setting_name {
+            SettingName::Language => {
+                let SettingVariant::Choice { ref value, .. } = setting.variant else {
+                    continue;
+                };
+                localization.set_language(value.value);
+            }
             SettingName::Volume => {
                 let SettingVariant::Slider { ref value, .. } = setting.variant else {
                     continue;
@@ -508,6 +724,41 @@ fn update_setting(
                 };
                 speedrun_timer.enabled = value.value;
             }
+            SettingName::AngleSnapping => {
+                let SettingVariant::Choice { ref value, .. } = setting.variant else {
+                    continue;
+                };
+                aim_settings.snapping = ANGLE_SNAPPING_OPTIONS[value.value].1;
+            }
+            SettingName::AimAssist => {
+                let SettingVariant::Toggle { ref value, .. } = setting.variant else {
+                    continue;
+                };
+                aim_settings.aim_assist = value.value;
+            }
+            SettingName::ShootInput | SettingName::SnapInput | SettingName::SneakInput => {
+                let SettingVariant::Choice { ref value, .. } = setting.variant else {
+                    continue;
+                };
+                let action = match setting_name {
+                    SettingName::ShootInput => HeldAction::Shoot,
+                    SettingName::SnapInput => HeldAction::Snap,
+                    _ => HeldAction::Sneak,
+                };
+                held_actions.set_mode(action, INPUT_MODE_OPTIONS[value.value].1);
+            }
+            SettingName::Rewind => {
+                let SettingVariant::Toggle { ref value, .. } = setting.variant else {
+                    continue;
+                };
+                rewind.set_enabled(value.value);
+            }
+            SettingName::SolutionHints => {
+                let SettingVariant::Toggle { ref value, .. } = setting.variant else {
+                    continue;
+                };
+                solution_hint.set_enabled(value.value);
+            }
         }
     }
 }
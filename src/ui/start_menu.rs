@@ -5,6 +5,8 @@ use crate::{
     sound::{BgmTrack, ChangeBgmEvent},
 };
 
+use super::localization::LocalizedText;
+
 pub struct StartMenuPlugin;
 
 impl Plugin for StartMenuPlugin {
@@ -74,7 +76,7 @@ fn spawn_start(
                         ..default()
                     },
                     font.clone().with_font_size(48.),
-                    Text::new("Play"),
+                    LocalizedText::new("start_menu.play"),
                     Button,
                     StartMenuButtonMarker::Play,
                 ))
@@ -85,7 +87,7 @@ fn spawn_start(
                         ..default()
                     },
                     font.clone().with_font_size(48.),
-                    Text::new("Settings"),
+                    LocalizedText::new("start_menu.settings"),
                     Button,
                     StartMenuButtonMarker::Settings,
                 ))
@@ -96,7 +98,7 @@ fn spawn_start(
                         ..default()
                     },
                     font.clone().with_font_size(48.),
-                    Text::new("Quit"),
+                    LocalizedText::new("start_menu.quit"),
                     Button,
                     StartMenuButtonMarker::Quit,
                 ));